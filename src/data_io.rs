@@ -1,26 +1,434 @@
-use std::io::{Result};
+use std::io::{self, Read, Write, Result};
 use std::fs::File;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::ffi::OsString;
+use std::{error, fmt};
+
+/// Extension, which encrypted files get in a mirrored directory
+pub const ENCRYPTED_EXTENSION: &str = "des";
+
+/// Path, which stands for standard input (when read) or standard
+/// output (when written)
+pub const STDIO_PATH: &str = "-";
+
+/// @returns whether the ‘path’ stands for standard input / output
+pub fn is_stdio<P: AsRef<Path>>(path: &P) -> bool {
+    path.as_ref() == Path::new(STDIO_PATH)
+}
+
+/// Possible ERRORS of opening the source and destination files:
+/// SameFile          - both paths lead to the same file (it would be
+///                     truncated before it’s read)
+/// DestinationExists - destination file exists, but it isn’t allowed
+///                     to be overwritten
+/// Io                - error of the file system
+#[derive(Debug)]
+pub enum OpenFilesError {
+    SameFile(PathBuf),
+    DestinationExists(PathBuf),
+    Io(io::Error),
+}
+
+impl fmt::Display for OpenFilesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> std::result::Result<(), fmt::Error> {
+        match self {
+            OpenFilesError::SameFile(path) =>
+                write!(f, "<{} is the source file itself>", path.display()),
+            OpenFilesError::DestinationExists(path) =>
+                write!(f, "<{} already exists, it can be overwritten by force>", path.display()),
+            OpenFilesError::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl error::Error for OpenFilesError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            OpenFilesError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for OpenFilesError {
+    fn from(error: io::Error) -> Self { OpenFilesError::Io(error) }
+}
+
+impl From<OpenFilesError> for io::Error {
+    fn from(error: OpenFilesError) -> Self {
+        match error {
+            OpenFilesError::Io(error) => error,
+            OpenFilesError::SameFile(_) => io::Error::new(io::ErrorKind::InvalidInput, error),
+            OpenFilesError::DestinationExists(_) => io::Error::new(io::ErrorKind::AlreadyExists, error),
+        }
+    }
+}
+
+/// @returns directory of the ‘path’ (’.’ for a bare file name)
+fn directory_of(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Canonical path of a file, which may not exist yet (then the path of
+/// its directory is canonical)
+/// @returns absolute path without links, ’.’ and ’..’
+fn canonical_path(path: &Path) -> Result<PathBuf> {
+    match path.canonicalize() {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            let name = match path.file_name() {
+                Some(name) => name,
+                None => return Err(error),
+            };
+            Ok(directory_of(path).canonicalize()?.join(name))
+        },
+        canonical => canonical,
+    }
+}
+
+/// Canonical path of a directory, which may not exist yet: the nearest
+/// existing ancestor is canonical, and the missing part is appended
+/// (it’s created as plain directories, so its ’..’ just steps back)
+/// @returns absolute path without links, ’.’ and ’..’
+fn canonical_dir_path(path: &Path) -> Result<PathBuf> {
+    for ancestor in path.ancestors() {
+        let existing = if ancestor == Path::new("") { Path::new(".") } else { ancestor };
+        match existing.canonicalize() {
+            Ok(mut canonical) => {
+                let missing = path.strip_prefix(ancestor).expect("Ancestor is a prefix");
+                for component in missing.components() {
+                    match component {
+                        Component::ParentDir => { canonical.pop(); },
+                        Component::Normal(name) => canonical.push(name),
+                        _ => (),
+                    }
+                }
+                return Ok(canonical);
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+            Err(error) => return Err(error),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::NotFound, "<no existing directory on the path>"))
+}
+
+/// Checks, whether the directory ‘inner’ (it may not exist yet) is
+/// ‘outer’ or is inside of it, before ‘inner’ is created
+/// @returns whether ‘inner’ is nested in the existing ‘outer’
+pub fn is_nested_dir<A, B>(inner: &A, outer: &B) -> Result<bool>
+where A: AsRef<Path>, B: AsRef<Path> {
+    Ok(canonical_dir_path(inner.as_ref())?.starts_with(outer.as_ref().canonicalize()?))
+}
+
+/// Paths are the same file, if their canonical paths are equal (e.g.
+/// ’./a’ and ’a’, or a symbolic link and its target), or if they’re
+/// hard links of the same device and inode
+/// @returns whether ‘first’ and ‘second’ lead to the same file
+pub fn is_same_file<A, B>(first: &A, second: &B) -> Result<bool>
+where A: AsRef<Path>, B: AsRef<Path> {
+    let (first, second) = (first.as_ref(), second.as_ref());
+    if canonical_path(first)? == canonical_path(second)? {
+        return Ok(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let (Ok(first), Ok(second)) = (std::fs::metadata(first), std::fs::metadata(second)) {
+            return Ok(first.dev() == second.dev() && first.ino() == second.ino());
+        }
+    }
+    Ok(false)
+}
 
 /// Opens the file at path ’read_filepath’ for read,
-/// Opens the file at path ’write_filepath’ for write or creates it,
+/// Opens the file at path ’write_filepath’ for write or creates it
+/// (an existing file is only truncated, if ‘overwrite’ is allowed),
+/// paths can’t lead to the same file
 /// @returns a Result, with a tuple of the read file handle and the
 /// write file handle
-pub fn open_rw_files<R, W>(to_read: &R, to_write: &W)
-                           -> Result<(File, File)>
+pub fn open_rw_files<R, W>(to_read: &R, to_write: &W, overwrite: bool)
+                           -> std::result::Result<(File, File), OpenFilesError>
 where R: AsRef<Path>, W: AsRef<Path> {
-    // TODO: don’t panic on same files, throw Result
     let read_path = to_read.as_ref();
     let write_path = to_write.as_ref();
-    assert!(read_path != write_path);
     let read_file = File::open(read_path)?;
-    let write_file = File::create(write_path)?;
-    return Ok( (read_file, write_file) );
+    if is_same_file(&read_path, &write_path)? {
+        return Err(OpenFilesError::SameFile(write_path.to_path_buf()));
+    }
+    Ok( (read_file, create_write_file(write_path, overwrite)?) )
+}
+
+/// Creates the file at ‘path’ for write (an existing file is only
+/// truncated, if ‘overwrite’ is allowed)
+/// @returns a Result with the write file handle
+fn create_write_file(path: &Path, overwrite: bool) -> std::result::Result<File, OpenFilesError> {
+    if overwrite {
+        return Ok(File::create(path)?);
+    }
+    // Checked and created at once, no other file can sneak in
+    match std::fs::OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => Ok(file),
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists =>
+            Err(OpenFilesError::DestinationExists(path.to_path_buf())),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Read and write streams, opened by ‘open_rw_streams’
+pub type Streams = (Box<dyn Read>, Box<dyn Write>);
+
+/// The same as ‘open_rw_files’, but ’-’ path stands for standard input
+/// (to read) or standard output (to write)
+/// @returns a Result, with a tuple of the read stream and the write
+/// stream
+pub fn open_rw_streams<R, W>(to_read: &R, to_write: &W, overwrite: bool)
+                             -> std::result::Result<Streams, OpenFilesError>
+where R: AsRef<Path>, W: AsRef<Path> {
+    match (is_stdio(to_read), is_stdio(to_write)) {
+        (false, false) => {
+            let (read_file, write_file) = open_rw_files(to_read, to_write, overwrite)?;
+            Ok( (Box::new(read_file), Box::new(write_file)) )
+        },
+        (read_stdio, write_stdio) => {
+            let read: Box<dyn Read> = if read_stdio {
+                Box::new(io::stdin().lock())
+            } else {
+                Box::new(File::open(to_read)?)
+            };
+            let write: Box<dyn Write> = if write_stdio {
+                Box::new(io::stdout().lock())
+            } else {
+                Box::new(create_write_file(to_write.as_ref(), overwrite)?)
+            };
+            Ok( (read, write) )
+        },
+    }
+}
+
+/// Replaces the file at ‘path’ by the result of ‘transform’ of it.
+/// The result is written to a temporary file of the same directory,
+/// synced to the disk and atomically renamed over the original (the
+/// permissions are kept). On any error the original stays untouched,
+/// and the temporary file is removed (an interrupted process may leave
+/// it, but still not the original). Once the file is renamed, it is
+/// replaced: failure to sync the directory (on Unix) is only a warning
+/// @returns a Result of I/O operations and of the ‘transform’, with
+/// the error of the directory sync, if the rename may not survive a crash
+pub fn transform_in_place<P, F>(path: &P, transform: F) -> Result<Option<io::Error>>
+where P: AsRef<Path>, F: FnOnce(&mut File, &mut File) -> Result<()> {
+    // Symbolic link is replaced through, not by the file
+    let path = path.as_ref().canonicalize()?;
+    let mut read_file = File::open(&path)?;
+    let metadata = read_file.metadata()?;
+    if !metadata.is_file() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "<not a file>"));
+    }
+    let directory = path.parent().expect("Canonical path of a file has a parent");
+    let name = path.file_name().expect("Canonical path of a file has a name");
+    let (temp_path, mut temp_file) = create_temp_file(directory, name)?;
+    let result = transform(&mut read_file, &mut temp_file)
+        .and_then(|_| temp_file.set_permissions(metadata.permissions()))
+        .and_then(|_| temp_file.sync_all())
+        .and_then(|_| std::fs::rename(&temp_path, &path));
+    if let Err(error) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(error);
+    }
+    // The rename itself is durable, when the directory is synced
+    #[cfg(unix)]
+    if let Err(error) = File::open(directory).and_then(|directory| directory.sync_all()) {
+        return Ok(Some(error));
+    }
+    Ok(None)
+}
+
+/// Writes the ‘data’ into the file at ‘path’ through a temporary file
+/// of the same directory, which is synced to the disk and atomically
+/// renamed over the file. The file has either the old data or the new
+/// one, even if the process is killed in the middle
+/// @returns a Result of I/O operations
+pub fn write_atomically<P: AsRef<Path>>(path: &P, data: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let name = path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "<not a file>"))?;
+    let (temp_path, mut temp_file) = create_temp_file(directory_of(path), name)?;
+    let result = temp_file.write_all(data)
+        .and_then(|_| temp_file.sync_all())
+        .and_then(|_| std::fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Creates a new hidden file in the ‘directory’, named after ‘name’
+/// @returns path and handle of the created file
+fn create_temp_file(directory: &Path, name: &std::ffi::OsStr) -> Result<(PathBuf, File)> {
+    for attempt in 0.. {
+        let mut temp_name = OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(".{}.{}.tmp", std::process::id(), attempt));
+        let temp_path = directory.join(temp_name);
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    unreachable!("Attempts are endless")
+}
+
+/// Walks the directory tree at ‘root’ (symbolic links to directories
+/// aren’t followed, so the walk can’t loop), in the order of names.
+/// A directory, that can’t be read, doesn’t stop the walk
+/// @returns paths of all the files (starting with ‘root’), and errors
+/// with paths of directories, that couldn’t be read
+pub fn walk_files<P: AsRef<Path>>(root: &P) -> (Vec<PathBuf>, Vec<(PathBuf, io::Error)>) {
+    let mut files = vec![];
+    let mut failures = vec![];
+    let mut directories = vec![root.as_ref().to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let entries = match std::fs::read_dir(&directory)
+            .and_then(|entries| entries.collect::<Result<Vec<_>>>()) {
+            Ok(entries) => entries,
+            Err(error) => {
+                failures.push((directory, error));
+                continue;
+            },
+        };
+        let mut paths: Vec<(PathBuf, bool)> = entries.iter()
+            .map(|entry| (entry.path(), entry.file_type().is_ok_and(|t| t.is_dir())))
+            .collect();
+        paths.sort();
+        // directories are popped from the end, so they go reversed
+        for (path, is_dir) in paths.into_iter().rev() {
+            if is_dir {
+                directories.push(path);
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    (files, failures)
+}
+
+/// Matches a name by a shell-like pattern: ‘*’ stands for any
+/// characters, ‘?’ for a single one
+/// @returns true if the whole ‘name’ matches
+fn matches_wildcard(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| matches_wildcard(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && matches_wildcard(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && matches_wildcard(rest, &name[1..]),
+    }
+}
+
+/// Expands ‘*’ and ‘?’ in the file name of the ‘pattern’ (as a shell
+/// does, when it’s not done by the shell). Directories of the pattern
+/// are taken literally, hidden files only match a pattern of a hidden
+/// name
+/// @returns matching files in order of names, or the path itself, if
+/// it has no wildcards
+pub fn expand_pattern<P: AsRef<Path>>(pattern: &P) -> Result<Vec<PathBuf>> {
+    let pattern = pattern.as_ref();
+    let name: Vec<char> = match pattern.file_name().and_then(|name| name.to_str()) {
+        Some(name) if name.contains(['*', '?']) => name.chars().collect(),
+        _ => return Ok(vec![pattern.to_path_buf()]),
+    };
+    let directory = directory_of(pattern);
+    let mut matches = vec![];
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let entry_name: Vec<char> = match entry.file_name().to_str() {
+            Some(entry_name) => entry_name.chars().collect(),
+            None => continue,
+        };
+        if (entry_name.first() == Some(&'.')) != (name.first() == Some(&'.')) {
+            continue;
+        }
+        if matches_wildcard(&name, &entry_name) && entry.path().is_file() {
+            matches.push(pattern.with_file_name(entry.file_name()));
+        }
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+/// Path of a file in the mirrored directory: the encrypted file gets
+/// ’.des’ after its whole name (so that ’a.txt’ and ’a.md’ don’t
+/// collide), and the decrypted one loses it (if it has it)
+/// @returns mirrored path, relative in the same way as ‘relative’
+pub fn mirrored_path<P: AsRef<Path>>(relative: &P, encrypt: bool) -> PathBuf {
+    let relative = relative.as_ref();
+    let mut mirrored = relative.to_path_buf();
+    if encrypt {
+        let mut name = relative.file_name().map(OsString::from).unwrap_or_default();
+        name.push(".");
+        name.push(ENCRYPTED_EXTENSION);
+        mirrored.set_file_name(name);
+    } else if relative.extension().is_some_and(|e| e == ENCRYPTED_EXTENSION) {
+        mirrored.set_extension("");
+    }
+    mirrored
+}
+
+/// Read / Write adapter, counting bytes, that went through it
+pub struct Counted<T> {
+    inner: T,
+    bytes: usize,
+}
+
+impl<T> Counted<T> {
+    pub fn new(inner: T) -> Self { Self { inner, bytes: 0 } }
+    /// @returns number of bytes read / written so far
+    pub fn transferred(&self) -> usize { self.bytes }
+}
+
+impl<T: Read> Read for Counted<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let size = self.inner.read(buf)?;
+        self.bytes += size;
+        Ok(size)
+    }
+}
+
+impl<T: Write> Write for Counted<T> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let size = self.inner.write(buf)?;
+        self.bytes += size;
+        Ok(size)
+    }
+    fn flush(&mut self) -> Result<()> { self.inner.flush() }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    /// Directory of a test in the system’s temporary one, which is
+    /// removed with its content on drop (i.e. even if the test panics)
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("des-{}-{}", name, std::process::id()));
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn path(&self) -> &Path { &self.0 }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
     #[test]
     fn test_open_rw_files() {
         // > test init
@@ -30,17 +438,17 @@ mod tests {
         // > test init end
 
         // read path is not a file
-        if !open_rw_files(&impossible_name_2, &impossible_name).is_err(){
+        if open_rw_files(&impossible_name_2, &impossible_name, false).is_ok(){
             std::fs::remove_file(impossible_name);
             panic!("Test fail: opening 'read' path, but file doesn't exist");
         }
         // write path is dir
         let dir = "./";
-        if !open_rw_files(&impossible_name, &dir).is_err() {
+        if open_rw_files(&impossible_name, &dir, false).is_ok() {
             std::fs::remove_file(impossible_name);
             panic!("Test fail: opening 'write' path, but it is directory");
         }
-        if !open_rw_files(&impossible_name, &impossible_name_2).is_ok(){
+        if open_rw_files(&impossible_name, &impossible_name_2, false).is_err(){
             std::fs::remove_file(impossible_name_2);
             std::fs::remove_file(impossible_name);
             panic!("Test fail: should normally open read file and \n\
@@ -50,4 +458,187 @@ mod tests {
         std::fs::remove_file(impossible_name_2);
         std::fs::remove_file(impossible_name);
     }
+
+    #[test]
+    fn test_same_file() {
+        let dir = TempDir::new("same");
+        let root = dir.path();
+        let (file, other) = (root.join("file"), root.join("other"));
+        std::fs::write(&file, b"data").unwrap();
+        std::fs::write(&other, b"data").unwrap();
+        let dotted = root.join(".").join("file");
+        let same_file = |path: &Path| open_rw_files(&file, &path, true);
+        let is_dotted = matches!(same_file(&dotted), Err(OpenFilesError::SameFile(_)));
+        let is_new_same = is_same_file(&root.join("new"), &root.join(".").join("new")).unwrap();
+        #[cfg(unix)]
+        let links = {
+            std::os::unix::fs::symlink(&file, root.join("symlink")).unwrap();
+            std::fs::hard_link(&file, root.join("hardlink")).unwrap();
+            (matches!(same_file(&root.join("symlink")), Err(OpenFilesError::SameFile(_))),
+             matches!(same_file(&root.join("hardlink")), Err(OpenFilesError::SameFile(_))))
+        };
+        let is_kept = matches!(open_rw_files(&file, &other, false),
+                               Err(OpenFilesError::DestinationExists(_)));
+        let other_data = std::fs::read(&other).unwrap();
+        let is_overwritten = open_rw_files(&file, &other, true).is_ok();
+        let file_data = std::fs::read(&file).unwrap();
+        assert!(is_dotted, "Different spelling of the same path is the same file");
+        assert!(is_new_same, "Files, which don't exist yet, are compared by directory");
+        #[cfg(unix)]
+        assert_eq!(links, (true, true), "Links to the same file are the same file");
+        assert!(is_kept && other_data == b"data", "Existing file isn't overwritten by default");
+        assert!(is_overwritten, "Existing file is overwritten by force");
+        assert_eq!(file_data, b"data", "Source file is never truncated");
+    }
+
+    #[test]
+    fn test_stdin_destination() {
+        let dir = TempDir::new("stdin");
+        let (existing, new) = (dir.path().join("existing"), dir.path().join("new"));
+        std::fs::write(&existing, b"data").unwrap();
+        let is_kept = matches!(open_rw_streams(&"-", &existing, false),
+                               Err(OpenFilesError::DestinationExists(_)));
+        let existing_data = std::fs::read(&existing).unwrap();
+        let is_created = open_rw_streams(&"-", &new, false).is_ok();
+        let is_overwritten = open_rw_streams(&"-", &existing, true).is_ok();
+        assert!(is_kept && existing_data == b"data",
+                "Existing file isn't overwritten by standard input by default");
+        assert!(is_created && new.is_file(), "New file is created for standard input");
+        assert!(is_overwritten && std::fs::read(&existing).unwrap().is_empty(),
+                "Existing file is overwritten by standard input by force");
+    }
+
+    #[test]
+    fn test_stdio_path() {
+        assert!(is_stdio(&"-"), "Dash stands for standard input / output");
+        assert!(!is_stdio(&"./-"), "Explicit path to a file named dash is a file");
+        assert!(!is_stdio(&"file.des"));
+    }
+
+    #[test]
+    fn test_transform_in_place() {
+        let dir = TempDir::new("in-place");
+        let root = dir.path();
+        let path = root.join("data");
+        std::fs::write(&path, b"abc").unwrap();
+        #[cfg(unix)]
+        use std::os::unix::fs::PermissionsExt;
+        #[cfg(unix)]
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let reverse = |read: &mut File, write: &mut File| {
+            let mut data = vec![];
+            read.read_to_end(&mut data)?;
+            data.reverse();
+            write.write_all(&data)
+        };
+        let reversed = transform_in_place(&path, reverse)
+            .map(|warning| (warning.is_none(), std::fs::read(&path).unwrap()));
+        let failed = transform_in_place(&path, |_: &mut File, write: &mut File| {
+            write.write_all(b"partial")?;
+            Err(io::Error::other("failure"))
+        });
+        let after_failure = std::fs::read(&path).unwrap();
+        #[cfg(unix)]
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        let entries = std::fs::read_dir(root).unwrap().count();
+        assert_eq!(reversed.unwrap(), (true, b"cba".to_vec()),
+                   "File should be replaced by the result, with its directory synced");
+        assert!(failed.is_err(), "Error of the transform should be returned");
+        assert_eq!(after_failure, b"cba", "Original should stay untouched on error");
+        assert_eq!(entries, 1, "Temporary file should be removed");
+        #[cfg(unix)]
+        assert_eq!(mode, 0o640, "Permissions should be kept");
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = TempDir::new("atomic");
+        let root = dir.path();
+        let path = root.join("checkpoint");
+        let written = write_atomically(&path, b"old").and_then(|_| write_atomically(&path, b"new"));
+        let data = std::fs::read(&path).unwrap();
+        let entries = std::fs::read_dir(root).unwrap().count();
+        let missing = write_atomically(&root.join("missing").join("checkpoint"), b"data");
+        assert!(written.is_ok());
+        assert_eq!(data, b"new", "File should be replaced by the new data");
+        assert_eq!(entries, 1, "Temporary file shouldn't be left");
+        assert!(missing.is_err(), "Missing directory is an error");
+    }
+
+    #[test]
+    fn test_nested_dir() {
+        let dir = TempDir::new("nested");
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        assert!(is_nested_dir(&root.join("src/out/deeper"), &root.join("src")).unwrap(),
+                "Missing output directory inside of the input one");
+        assert!(is_nested_dir(&root.join("new/../src/out"), &root.join("src")).unwrap(),
+                "’..’ of the missing part steps back");
+        assert!(is_nested_dir(&root.join("src"), &root.join("src")).unwrap(), "Same directory");
+        assert!(!is_nested_dir(&root.join("srcs/out"), &root.join("src")).unwrap(),
+                "Common prefix of names isn't nesting");
+        assert!(!root.join("src/out").exists() && !root.join("new").exists(),
+                "Nothing is created by the check");
+        assert!(is_nested_dir(&root.join("out"), &root.join("missing")).is_err(),
+                "Missing input directory is an error");
+    }
+
+    #[test]
+    fn test_walk_files() {
+        let dir = TempDir::new("walk");
+        let root = dir.path().to_path_buf();
+        for dir in ["a/b", "c"].iter() {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["z.txt", "a/b/x.bin", "a/y"].iter() {
+            File::create(root.join(file)).unwrap();
+        }
+        let (files, failures) = walk_files(&root);
+        drop(dir);
+        let relative: Vec<_> = files.iter().map(|f| f.strip_prefix(&root).unwrap()).collect();
+        assert_eq!(relative, [Path::new("a/b/x.bin"), Path::new("a/y"), Path::new("z.txt")],
+                   "Every file of the tree should be found, in order");
+        assert!(failures.is_empty(), "Every directory is readable");
+        let (files, failures) = walk_files(&root);
+        assert!(files.is_empty() && failures.len() == 1, "Missing root is a failure");
+    }
+
+    #[test]
+    fn test_expand_pattern() {
+        let dir = TempDir::new("pattern");
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("sub.txt")).unwrap();
+        for file in ["b.txt", "a.txt", "a.md", ".c.txt"].iter() {
+            File::create(root.join(file)).unwrap();
+        }
+        let texts = expand_pattern(&root.join("*.txt"));
+        let single = expand_pattern(&root.join("?.md"));
+        let literal = expand_pattern(&root.join("missing"));
+        let nothing = expand_pattern(&root.join("*.bin"));
+        assert_eq!(texts.unwrap(), [root.join("a.txt"), root.join("b.txt")],
+                   "Hidden files and directories don't match");
+        assert_eq!(single.unwrap(), [root.join("a.md")]);
+        assert_eq!(literal.unwrap(), [root.join("missing")], "Path without wildcards stays as it is");
+        assert!(nothing.unwrap().is_empty());
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert!(matches_wildcard(&chars("a*b?"), &chars("axxbc")));
+        assert!(!matches_wildcard(&chars("a*b"), &chars("abc")));
+    }
+
+    #[test]
+    fn test_mirrored_path() {
+        assert_eq!(mirrored_path(&"a/b.txt", true), Path::new("a/b.txt.des"));
+        assert_eq!(mirrored_path(&"a/b", true), Path::new("a/b.des"));
+        assert_eq!(mirrored_path(&"a/b.txt.des", false), Path::new("a/b.txt"));
+        assert_eq!(mirrored_path(&"a/b.txt", false), Path::new("a/b.txt"),
+                   "Decrypted file keeps the name without the extension");
+    }
+
+    #[test]
+    fn test_counted() {
+        let mut read = Counted::new(&[1u8, 2, 3, 4, 5][..]);
+        let mut write = Counted::new(vec![]);
+        std::io::copy(&mut read, &mut write).unwrap();
+        assert_eq!((read.transferred(), write.transferred()), (5, 5), "Bytes weren't counted");
+    }
 }
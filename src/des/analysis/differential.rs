@@ -0,0 +1,288 @@
+//! Differential properties of S-boxes. Difference distribution table
+//! (DDT) of an S-box counts, for every input difference, how many
+//! inputs lead to every output difference. The highest counts are what
+//! differential cryptanalysis builds its characteristics on. Besides
+//! the tables, S-box design criteria of DES (as published by
+//! Coppersmith) are checked
+
+use super::{SBox, SBOX_INPUTS, SBOX_OUTPUTS};
+
+/// Counts of output differences (columns) for every input difference
+/// (rows)
+pub type DifferenceTable = [[u32; SBOX_OUTPUTS]; SBOX_INPUTS];
+
+/// Builds difference distribution table of an S-box
+/// @returns table, where [dx][dy] is a number of inputs x, such that
+/// S(x) ^ S(x ^ dx) == dy
+pub fn difference_distribution(sbox: &SBox) -> DifferenceTable {
+    let mut table = [[0; SBOX_OUTPUTS]; SBOX_INPUTS];
+    for (input_difference, row) in table.iter_mut().enumerate() {
+        for input in 0..SBOX_INPUTS {
+            let output_difference = sbox[input] ^ sbox[input ^ input_difference];
+            row[output_difference as usize] += 1;
+        }
+    }
+    table
+}
+
+/// The most probable differential of an S-box
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Differential {
+    pub input_difference: u8,
+    pub output_difference: u8,
+    /// Number of inputs (out of 64), that follow the differential
+    pub count: u32,
+}
+
+impl Differential {
+    pub fn probability(&self) -> f64 {
+        self.count as f64 / SBOX_INPUTS as f64
+    }
+}
+
+/// Finds the differential with the highest count among nonzero input
+/// differences (the first one, if there are several)
+/// @returns the most probable differential
+pub fn best_differential(table: &DifferenceTable) -> Differential {
+    let mut best = Differential { input_difference: 0, output_difference: 0, count: 0 };
+    for (input_difference, row) in table.iter().enumerate().skip(1) {
+        for (output_difference, &count) in row.iter().enumerate() {
+            if count > best.count {
+                best = Differential {
+                    input_difference: input_difference as u8,
+                    output_difference: output_difference as u8,
+                    count,
+                };
+            }
+        }
+    }
+    best
+}
+
+/// Result of checking of a single design criterion
+#[derive(Clone, Debug)]
+pub struct CriterionCheck {
+    /// Name, as Coppersmith numbers the criteria (S-2 .. S-6)
+    pub name: &'static str,
+    pub description: &'static str,
+    pub holds: bool,
+}
+
+/// Checks S-box design criteria of DES, which can be checked on a
+/// single S-box
+/// @returns result for every criterion
+pub fn check_design_criteria(sbox: &SBox) -> Vec<CriterionCheck> {
+    let differs_in_two_bits = |input: usize, difference: usize| {
+        (sbox[input] ^ sbox[input ^ difference]).count_ones() >= 2
+    };
+    let rows_are_permutations = (0..4).all(|row| {
+        let mut seen = [false; SBOX_OUTPUTS];
+        for column in 0..SBOX_OUTPUTS {
+            let input = ((row & 0b10) << 4) | (column << 1) | (row & 1);
+            seen[sbox[input] as usize] = true;
+        }
+        seen.iter().all(|&s| s)
+    });
+    let one_bit_flips_two = (0..SBOX_INPUTS).all(|input| {
+        (0..6).all(|bit| differs_in_two_bits(input, 1 << bit))
+    });
+    let middle_bits_flip_two = (0..SBOX_INPUTS).all(|input| {
+        differs_in_two_bits(input, 0b001100)
+    });
+    let outer_pair_changes = (0..SBOX_INPUTS).all(|input| {
+        (0..4).all(|ef| sbox[input] != sbox[input ^ (0b110000 | ef << 2)])
+    });
+    let table = difference_distribution(sbox);
+    let no_frequent_difference = table.iter().skip(1)
+        .all(|row| row.iter().all(|&count| count <= 16));
+    vec![
+        CriterionCheck {
+            name: "S-2",
+            description: "each row is a permutation of 0..15",
+            holds: rows_are_permutations,
+        },
+        CriterionCheck {
+            name: "S-3",
+            description: "one bit input change flips at least two output bits",
+            holds: one_bit_flips_two,
+        },
+        CriterionCheck {
+            name: "S-4",
+            description: "input change in the two middle bits (001100) flips at least two output bits",
+            holds: middle_bits_flip_two,
+        },
+        CriterionCheck {
+            name: "S-5",
+            description: "input change 11ef00 always changes the output",
+            holds: outer_pair_changes,
+        },
+        CriterionCheck {
+            name: "S-6",
+            description: "no nonzero input difference leads to one output difference for more than 8 pairs",
+            holds: no_frequent_difference,
+        },
+    ]
+}
+
+/// Differential properties of a single S-box
+#[derive(Clone, Debug)]
+pub struct SBoxReport {
+    pub name: String,
+    pub table: DifferenceTable,
+    pub best: Differential,
+    pub criteria: Vec<CriterionCheck>,
+}
+
+/// Differential properties of a set of S-boxes
+#[derive(Clone, Debug)]
+pub struct DifferentialReport {
+    pub sboxes: Vec<SBoxReport>,
+}
+
+/// Analyzes a set of S-boxes, they’re named S1, S2, ... in order
+/// @returns report of every S-box
+pub fn analyze(sboxes: &[SBox]) -> DifferentialReport {
+    DifferentialReport {
+        sboxes: sboxes.iter().enumerate().map(|(i, sbox)| {
+            let table = difference_distribution(sbox);
+            SBoxReport {
+                name: format!("S{}", i + 1),
+                best: best_differential(&table),
+                table,
+                criteria: check_design_criteria(sbox),
+            }
+        }).collect(),
+    }
+}
+
+impl DifferentialReport {
+    /// @returns the highest differential probability among all S-boxes
+    pub fn max_probability(&self) -> f64 {
+        self.sboxes.iter().map(|s| s.best.probability()).fold(0.0, f64::max)
+    }
+
+    /// Human readable report
+    /// @returns text with a summary and a table per S-box
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for sbox in self.sboxes.iter() {
+            text += &format!(
+                "{}: max differential probability {}/{} = {:.4} ({:#04X} -> {:#03X})\n",
+                sbox.name, sbox.best.count, SBOX_INPUTS, sbox.best.probability(),
+                sbox.best.input_difference, sbox.best.output_difference);
+            for criterion in sbox.criteria.iter() {
+                text += &format!("  [{}] {}: {}\n",
+                                 if criterion.holds { " ok " } else { "FAIL" },
+                                 criterion.name, criterion.description);
+            }
+            text += "\n  dx\\dy";
+            for output_difference in 0..SBOX_OUTPUTS {
+                text += &format!("{:>3X}", output_difference);
+            }
+            text += "\n";
+            for (input_difference, row) in sbox.table.iter().enumerate() {
+                text += &format!("  {:#04X} ", input_difference);
+                for count in row.iter() {
+                    text += &format!("{:>3}", count);
+                }
+                text += "\n";
+            }
+            text += "\n";
+        }
+        text += &format!("Max differential probability over all S-boxes: {:.4}\n",
+                         self.max_probability());
+        text
+    }
+
+    /// Machine readable tables, a row per input difference of each
+    /// S-box
+    /// @returns CSV with a header
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("sbox,input_difference");
+        for output_difference in 0..SBOX_OUTPUTS {
+            csv += &format!(",{}", output_difference);
+        }
+        csv += "\n";
+        for sbox in self.sboxes.iter() {
+            for (input_difference, row) in sbox.table.iter().enumerate() {
+                csv += &format!("{},{}", sbox.name, input_difference);
+                for count in row.iter() {
+                    csv += &format!(",{}", count);
+                }
+                csv += "\n";
+            }
+        }
+        csv
+    }
+
+    /// Machine readable report
+    /// @returns JSON object with tables, best differentials and
+    /// criteria of every S-box
+    pub fn to_json(&self) -> String {
+        let sboxes: Vec<String> = self.sboxes.iter().map(|sbox| {
+            let rows: Vec<String> = sbox.table.iter().map(|row| {
+                let counts: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+                format!("[{}]", counts.join(", "))
+            }).collect();
+            let criteria: Vec<String> = sbox.criteria.iter()
+                .map(|c| format!("\"{}\": {}", c.name, c.holds)).collect();
+            format!(
+                "    {{\"name\": \"{}\", \"max_probability\": {:.6}, \
+                 \"best\": {{\"input_difference\": {}, \"output_difference\": {}, \"count\": {}}}, \
+                 \"criteria\": {{{}}},\n     \"ddt\": [\n      {}\n     ]}}",
+                sbox.name, sbox.best.probability(), sbox.best.input_difference,
+                sbox.best.output_difference, sbox.best.count, criteria.join(", "),
+                rows.join(",\n      "))
+        }).collect();
+        format!("{{\n  \"max_probability\": {:.6},\n  \"sboxes\": [\n{}\n  ]\n}}\n",
+                self.max_probability(), sboxes.join(",\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::des_sboxes;
+
+    #[test]
+    fn test_des_difference_tables() {
+        for (i, sbox) in des_sboxes().iter().enumerate() {
+            let table = difference_distribution(sbox);
+            assert_eq!(table[0][0], 64, "S{}: zero difference is kept by every input", i + 1);
+            assert!(table.iter().all(|row| row.iter().sum::<u32>() == 64),
+                    "S{}: every row should count all 64 inputs", i + 1);
+            assert!(table.iter().flatten().all(|count| count % 2 == 0),
+                    "S{}: inputs come in pairs, so counts should be even", i + 1);
+        }
+        // Biham and Shamir’s example: S1, 0x34 -> 0x2 holds for 16 inputs
+        let table = difference_distribution(&des_sboxes()[0]);
+        assert_eq!(table[0x34][0x2], 16, "S1 DDT entry of the classic example");
+    }
+
+    #[test]
+    fn test_des_criteria_and_probability() {
+        let report = analyze(&des_sboxes());
+        assert_eq!(report.max_probability(), 0.25, "DES max differential probability is 1/4");
+        for sbox in report.sboxes.iter() {
+            for criterion in sbox.criteria.iter() {
+                assert!(criterion.holds, "{} violates {}", sbox.name, criterion.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_weak_sbox() {
+        // output is just the inner bits of the input: linear, so a
+        // difference always propagates with probability 1
+        let mut weak = [0; SBOX_INPUTS];
+        for (input, output) in weak.iter_mut().enumerate() {
+            *output = ((input >> 1) & 0xF) as u8;
+        }
+        let report = analyze(&[weak]);
+        assert_eq!(report.sboxes[0].best.count, 64, "Linear S-box differential");
+        assert!(report.sboxes[0].criteria.iter().any(|c| !c.holds),
+                "Linear S-box shouldn't meet design criteria");
+        assert_eq!(report.to_csv().lines().count(), 1 + SBOX_INPUTS, "CSV rows");
+        assert!(report.to_json().contains("\"S-3\": false"), "JSON criteria");
+    }
+}
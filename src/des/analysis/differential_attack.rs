@@ -0,0 +1,573 @@
+//! Biham and Shamir’s differential cryptanalysis of reduced round DES,
+//! for training purposes. Characteristics are searched over the
+//! engine’s own S-boxes, expansion and permutation (at most a single
+//! S-box is active per round). Then, for a random key, pairs of chosen
+//! plaintexts with the characteristic’s difference are encrypted and
+//! - every 6 bit part of the last round key is counted over the pairs,
+//!   for S-boxes, whose output difference in the last round is known
+//!   from the characteristic (i.e. S-boxes, which aren’t affected by
+//!   the unknown round in between)
+//! - the 8 key bits, which don’t enter the last round, are brute forced
+//!
+//! An n round attack uses characteristics over n - 3 rounds: the round
+//! n - 1 passes the difference as is, the round n - 2 is only known to
+//! change outputs of its active S-boxes
+
+use super::{des_sboxes, differential, Random, SBox, SBOXES_NUMBER, SBOX_INPUTS, SBOX_OUTPUTS};
+use super::{input_halves, output_halves, sbox_input, sbox_input_shift, sbox_output_mask};
+use super::differential::DifferenceTable;
+use crate::des::details::{des_network, encrypt_block_separately, KeyScheduler};
+use crate::des::details::{INITIAL_PERMUTATION, REVERSE_PERMUTATION};
+
+const BITS_IN_HALF: u32 = 32;
+const BITS_IN_BLOCK: u32 = 64;
+const SBOX_INPUT_MASK: u64 = (SBOX_INPUTS - 1) as u64;
+/// Limit of last round key candidates, which are brute forced (every
+/// one of them is completed by 256 values of the missing key bits)
+const MAX_ROUND_KEY_CANDIDATES: usize = 1 << 10;
+/// Chosen pairs per characteristic are 16 / probability by default
+const DEFAULT_PAIRS_FACTOR: f64 = 16.0;
+pub const MIN_ROUNDS: usize = 4;
+pub const MAX_ROUNDS: usize = 6;
+
+/// Differential of the round function through a single S-box: input
+/// difference leads to output difference for ‘count’ inputs out of 64
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RoundDifferential {
+    /// Index of S-box (0 for S1)
+    pub sbox: usize,
+    pub input_difference: u8,
+    pub output_difference: u8,
+    /// Entry of the S-box’s difference distribution table
+    pub count: u32,
+}
+
+impl RoundDifferential {
+    /// @returns difference of the round function’s output (32 bit half)
+    pub fn output_data_difference(&self) -> u32 {
+        sbox_output_mask(self.sbox, self.output_difference)
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.count as f64 / SBOX_INPUTS as f64
+    }
+}
+
+/// Differential characteristic of several rounds. Rounds with zero
+/// input difference hold with probability 1 and are ‘None’
+#[derive(Clone, Debug)]
+pub struct Characteristic {
+    pub rounds: Vec<Option<RoundDifferential>>,
+    /// (high, low) differences of halves entering the first round
+    pub input_difference: (u32, u32),
+    /// (high, low) differences of halves leaving the last round
+    pub output_difference: (u32, u32),
+}
+
+impl Characteristic {
+    pub fn probability(&self) -> f64 {
+        self.rounds.iter().flatten().map(|r| r.probability()).product()
+    }
+
+    /// @returns difference of the whole block entering the first round
+    pub fn block_difference(&self) -> u64 {
+        ((self.input_difference.0 as u64) << BITS_IN_HALF) | self.input_difference.1 as u64
+    }
+
+    /// S-boxes, which get nonzero difference in the round following the
+    /// characteristic. Outputs of others can be traced to the last round
+    /// @returns indices of active S-boxes
+    pub fn next_active_sboxes(&self) -> Vec<usize> {
+        (0..SBOXES_NUMBER).filter(|&s| sbox_input(self.output_difference.1, s) != 0).collect()
+    }
+
+    /// @returns compact description of differentials of every round
+    pub fn describe(&self) -> String {
+        let rounds: Vec<String> = self.rounds.iter().map(|round| match round {
+            Some(r) => format!("S{} {:#04X}->{:#03X} ({}/64)", r.sbox + 1,
+                               r.input_difference, r.output_difference, r.count),
+            None => "0->0".to_string(),
+        }).collect();
+        rounds.join(", ")
+    }
+}
+
+/// Differences of a half, which make exactly one S-box active
+/// @returns (difference, S-box) for all such differences
+fn single_sbox_differences() -> Vec<(u32, usize)> {
+    let exclusive_bits = |sbox: usize| -> Vec<u32> {
+        (0..BITS_IN_HALF).map(|bit| 1 << bit)
+            .filter(|&bit| (0..SBOXES_NUMBER).all(|s| (sbox_input(bit, s) != 0) == (s == sbox)))
+            .collect()
+    };
+    let mut differences = vec![];
+    for sbox in 0..SBOXES_NUMBER {
+        let bits = exclusive_bits(sbox);
+        for subset in 1..(1usize << bits.len()) {
+            let difference = bits.iter().enumerate()
+                .filter(|&(i, _)| (subset >> i) & 1 == 1)
+                .fold(0, |difference, (_, &bit)| difference | bit);
+            differences.push((difference, sbox));
+        }
+    }
+    differences
+}
+
+/// Exhaustive search of characteristics with at most one active S-box
+/// per round. Input difference to the round i + 1 is the input
+/// difference to the round i - 1 xor output difference of the round i,
+/// only the first two are free
+struct CharacteristicSearch {
+    tables: Vec<DifferenceTable>,
+    rounds: usize,
+    /// Inputs to a round, which keep at most one S-box active
+    inputs: Vec<(u32, Option<usize>)>,
+    found: Vec<Characteristic>,
+}
+
+impl CharacteristicSearch {
+    fn new(sboxes: &[SBox], rounds: usize) -> Self {
+        let mut inputs = vec![(0, None)];
+        inputs.extend(single_sbox_differences().into_iter().map(|(d, s)| (d, Some(s))));
+        Self {
+            tables: sboxes.iter().map(differential::difference_distribution).collect(),
+            rounds,
+            inputs,
+            found: vec![],
+        }
+    }
+
+    /// @returns possible differentials of the round function for an
+    /// input difference, which has at most one active S-box
+    fn round_differentials(&self, input: (u32, Option<usize>)) -> Vec<Option<RoundDifferential>> {
+        let sbox = match input.1 {
+            None => return vec![None],
+            Some(sbox) => sbox,
+        };
+        let input_difference = sbox_input(input.0, sbox);
+        (0..SBOX_OUTPUTS).filter_map(|output_difference| {
+            let count = self.tables[sbox][input_difference as usize][output_difference];
+            if count == 0 { return None; }
+            Some(Some(RoundDifferential {
+                sbox,
+                input_difference,
+                output_difference: output_difference as u8,
+                count,
+            }))
+        }).collect()
+    }
+
+    fn run(mut self) -> Vec<Characteristic> {
+        for &first in self.inputs.clone().iter() {
+            for differential in self.round_differentials(first) {
+                let output = differential.map_or(0, |d| d.output_data_difference());
+                // the left half of the plaintext is chosen so, that the
+                // second round gets any wanted difference
+                for &second in self.inputs.clone().iter() {
+                    if first.0 == 0 && second.0 == 0 {
+                        continue;
+                    }
+                    let mut rounds = vec![differential];
+                    let input_difference = (second.0 ^ output, first.0);
+                    self.extend(&mut rounds, input_difference, first.0, second);
+                }
+            }
+        }
+        self.found
+    }
+
+    /// Continues a characteristic, which has ‘rounds’ already, and the
+    /// previous round’s input difference is ‘previous’
+    fn extend(&mut self, rounds: &mut Vec<Option<RoundDifferential>>,
+              input_difference: (u32, u32), previous: u32, current: (u32, Option<usize>)) {
+        if rounds.len() == self.rounds {
+            self.found.push(Characteristic {
+                rounds: rounds.clone(),
+                input_difference,
+                output_difference: (previous, current.0),
+            });
+            return;
+        }
+        for differential in self.round_differentials(current) {
+            let next = previous ^ differential.map_or(0, |d| d.output_data_difference());
+            let is_last = rounds.len() + 1 == self.rounds;
+            let next = if is_last {
+                Some((next, None))
+            } else {
+                self.inputs.iter().find(|&&(d, _)| d == next).cloned()
+            };
+            if let Some(next) = next {
+                rounds.push(differential);
+                self.extend(rounds, input_difference, current.0, next);
+                rounds.pop();
+            }
+        }
+    }
+}
+
+/// Chooses characteristics to attack every S-box of the last round of
+/// ‘rounds’ round DES: for every S-box the most probable one, that
+/// leaves the S-box unaffected. An S-box, that no characteristic leaves
+/// unaffected, is brute forced
+/// @returns characteristics and S-boxes, which are attacked with them
+pub fn choose_characteristics(sboxes: &[SBox], rounds: usize) -> Vec<(Characteristic, Vec<usize>)> {
+    assert!(rounds >= MIN_ROUNDS, "Characteristics leave 3 rounds for the attack");
+    let mut characteristics = CharacteristicSearch::new(sboxes, rounds - 3).run();
+    characteristics.sort_by(|a, b| {
+        b.probability().partial_cmp(&a.probability()).unwrap()
+            .then(a.next_active_sboxes().len().cmp(&b.next_active_sboxes().len()))
+    });
+    let mut is_covered = [false; SBOXES_NUMBER];
+    let mut chosen = vec![];
+    for characteristic in characteristics {
+        let active = characteristic.next_active_sboxes();
+        let targets: Vec<usize> = (0..SBOXES_NUMBER)
+            .filter(|s| !is_covered[*s] && !active.contains(s)).collect();
+        if targets.is_empty() {
+            continue;
+        }
+        for &target in targets.iter() {
+            is_covered[target] = true;
+        }
+        chosen.push((characteristic, targets));
+        if is_covered.iter().all(|&c| c) {
+            break;
+        }
+    }
+    chosen
+}
+
+/// @returns 4 bit output difference of an S-box, taken from difference
+/// of the round function’s output (i.e. inverting the permutation P)
+fn sbox_output_difference(difference: u32, sbox: usize) -> u8 {
+    (0..4).filter(|&bit| difference & sbox_output_mask(sbox, 1 << bit) != 0)
+        .fold(0, |output, bit| output | (1 << bit))
+}
+
+/// Pair of chosen plaintexts and their ciphertexts, the ciphertexts are
+/// taken without the reverse permutation
+#[derive(Copy, Clone, Debug)]
+struct ChosenPair {
+    plaintext: u64,
+    ciphertexts: (u64, u64),
+}
+
+/// Encryption oracle of ‘rounds’ round DES under a secret key
+struct Oracle {
+    rounds: usize,
+    key: u64,
+    encrypted: usize,
+}
+
+impl Oracle {
+    /// @returns ciphertext of a chosen plaintext
+    fn encrypt(&mut self, plaintext: u64) -> u64 {
+        self.encrypted += 1;
+        des_network(self.rounds).encrypt_block(plaintext, self.key)
+    }
+
+    /// Encrypts ‘count’ random pairs, which differ by the
+    /// characteristic’s input difference after the initial permutation
+    fn chosen_pairs(&mut self, characteristic: &Characteristic, count: usize,
+                    random: &mut Random) -> Vec<ChosenPair> {
+        (0..count).map(|_| {
+            let permuted = random.next_u64();
+            let plaintext = REVERSE_PERMUTATION.apply(permuted);
+            let other = REVERSE_PERMUTATION.apply(permuted ^ characteristic.block_difference());
+            let ciphertexts = (INITIAL_PERMUTATION.apply(self.encrypt(plaintext)),
+                               INITIAL_PERMUTATION.apply(self.encrypt(other)));
+            ChosenPair { plaintext, ciphertexts }
+        }).collect()
+    }
+}
+
+/// Counts every 6 bit key of the last round for each of ‘targets’
+/// S-boxes: a key is suggested by a pair, if it makes the S-box output
+/// the difference, which is expected from the characteristic. Pairs,
+/// which certainly don’t follow the characteristic, are filtered out
+/// @returns counts per target S-box and number of pairs, that remained
+fn count_keys(sboxes: &[SBox], tables: &[DifferenceTable], characteristic: &Characteristic,
+              targets: &[usize], pairs: &[ChosenPair]) -> (Vec<[usize; SBOX_INPUTS]>, usize) {
+    let mut counts = vec![[0; SBOX_INPUTS]; targets.len()];
+    let mut right_pairs = 0;
+    for pair in pairs.iter() {
+        let (high, low) = output_halves(pair.ciphertexts.0);
+        let (other_high, other_low) = output_halves(pair.ciphertexts.1);
+        // output difference of the last round function is the low half
+        // difference xor the high half difference of the characteristic
+        // (passed as is by the round n - 1)
+        let output_difference = low ^ other_low ^ characteristic.output_difference.0;
+        let inputs: Vec<(u8, u8, u8)> = targets.iter().map(|&sbox| {
+            (sbox_input(high, sbox), sbox_input(other_high, sbox),
+             sbox_output_difference(output_difference, sbox))
+        }).collect();
+        let is_possible = targets.iter().zip(inputs.iter()).all(|(&sbox, &(x, y, output))| {
+            tables[sbox][(x ^ y) as usize][output as usize] != 0
+        });
+        if !is_possible {
+            continue;
+        }
+        right_pairs += 1;
+        for ((&sbox, &(x, y, output)), counts) in targets.iter().zip(inputs.iter())
+            .zip(counts.iter_mut()) {
+            for (key, count) in counts.iter_mut().enumerate() {
+                let sbox_table = &sboxes[sbox];
+                if sbox_table[(x as usize) ^ key] ^ sbox_table[(y as usize) ^ key] == output {
+                    *count += 1;
+                }
+            }
+        }
+    }
+    (counts, right_pairs)
+}
+
+/// Where key bits go in the last round key
+struct KeyBits {
+    /// (bit of the key, bit of the last round key)
+    last_round: Vec<(u32, u32)>,
+    /// Bits of the key, which are used, but not in the last round
+    missing: Vec<u32>,
+}
+
+impl KeyBits {
+    /// Key schedule only permutes bits, so each bit is traced alone
+    fn new(rounds: usize) -> Self {
+        let network = des_network(rounds);
+        let mut last_round = vec![];
+        let mut missing = vec![];
+        for bit in 0..BITS_IN_BLOCK {
+            let round_key = network.encrypting_keys(1 << bit)[rounds - 1].value;
+            if round_key != 0 {
+                last_round.push((bit, round_key.trailing_zeros()));
+            } else if KeyScheduler::inner_keys(1 << bit, 0)[0].value != 0 {
+                missing.push(bit);
+            }
+        }
+        Self { last_round, missing }
+    }
+
+    /// @returns key with bits taken from the last round key, the others
+    /// are taken from ‘missing_bits’ in order
+    fn key(&self, last_round_key: u64, missing_bits: usize) -> u64 {
+        let from_round = self.last_round.iter()
+            .filter(|&&(_, round_bit)| (last_round_key >> round_bit) & 1 == 1)
+            .fold(0, |key, &(bit, _)| key | (1 << bit));
+        self.missing.iter().enumerate()
+            .filter(|&(i, _)| (missing_bits >> i) & 1 == 1)
+            .fold(from_round, |key, (_, &bit)| key | (1 << bit))
+    }
+}
+
+/// Outcome of the attack on a single key
+#[derive(Clone, Debug)]
+pub struct DifferentialAttackResult {
+    pub key: u64,
+    /// Key found by the attack, parity bits are zero
+    pub recovered_key: Option<u64>,
+    /// Number of chosen pairs (encryptions are twice as many)
+    pub pairs: usize,
+    /// Number of pairs, which passed filtering
+    pub right_pairs: usize,
+    /// Number of last round key candidates, which were brute forced
+    pub candidates: usize,
+    /// Whether the recovered key encrypts like the secret one by full
+    /// DES, i.e. they’re equal up to parity bits
+    pub is_correct: bool,
+}
+
+/// Recovers the key of ‘rounds’ round DES by ‘characteristics’ (see
+/// ‘choose_characteristics’), using ‘pairs’ chosen pairs per
+/// characteristic
+fn attack(rounds: usize, key: u64, characteristics: &[(Characteristic, Vec<usize>)],
+          pairs: &[usize], random: &mut Random) -> DifferentialAttackResult {
+    let sboxes = des_sboxes();
+    let tables: Vec<DifferenceTable> = sboxes.iter()
+        .map(differential::difference_distribution).collect();
+    let mut oracle = Oracle { rounds, key, encrypted: 0 };
+    let mut candidates: Vec<Vec<u8>> = vec![(0..SBOX_INPUTS as u8).collect(); SBOXES_NUMBER];
+    let mut right_pairs = 0;
+    let mut known = None;
+    for ((characteristic, targets), &count) in characteristics.iter().zip(pairs.iter()) {
+        let chosen = oracle.chosen_pairs(characteristic, count, random);
+        known = known.or_else(|| chosen.first().cloned());
+        let (counts, right) = count_keys(&sboxes, &tables, characteristic, targets, &chosen);
+        right_pairs += right;
+        for (&sbox, counts) in targets.iter().zip(counts.iter()) {
+            let max = counts.iter().max().cloned().unwrap_or(0);
+            candidates[sbox] = (0..SBOX_INPUTS).filter(|&k| counts[k] == max)
+                .map(|k| k as u8).collect();
+        }
+    }
+    let candidates_number: usize = candidates.iter().map(|c| c.len()).product();
+    let mut result = DifferentialAttackResult {
+        key,
+        recovered_key: None,
+        pairs: oracle.encrypted / 2,
+        right_pairs,
+        candidates: candidates_number,
+        is_correct: false,
+    };
+    let known = match known {
+        Some(known) if candidates_number <= MAX_ROUND_KEY_CANDIDATES => known,
+        _ => return result,
+    };
+    let key_bits = KeyBits::new(rounds);
+    let network = des_network(rounds);
+    let expected = REVERSE_PERMUTATION.apply(known.ciphertexts.0);
+    for index in 0..candidates_number {
+        let mut rest = index;
+        let round_key = candidates.iter().enumerate().fold(0, |round_key, (sbox, c)| {
+            let bits = c[rest % c.len()] as u64;
+            rest /= c.len();
+            round_key | (bits << sbox_input_shift(sbox))
+        });
+        let found = (0..1 << key_bits.missing.len())
+            .map(|missing| key_bits.key(round_key, missing))
+            .find(|&guess| network.encrypt_block(known.plaintext, guess) == expected);
+        if let Some(found) = found {
+            result.recovered_key = Some(found);
+            break;
+        }
+    }
+    if let Some(found) = result.recovered_key {
+        // a different 56 bit key would give a different ciphertext of
+        // full DES, with overwhelming probability
+        let block = random.next_u64();
+        result.is_correct = encrypt_block_separately(block, found)
+            == encrypt_block_separately(block, key);
+    }
+    result
+}
+
+/// @returns chosen pairs per characteristic, which are enough to tell
+/// the right key from noise
+pub fn default_pairs(characteristic: &Characteristic) -> usize {
+    (DEFAULT_PAIRS_FACTOR / characteristic.probability()).ceil() as usize
+}
+
+/// Results of the attack on several keys
+#[derive(Clone, Debug)]
+pub struct DifferentialAttackReport {
+    pub rounds: usize,
+    pub characteristics: Vec<(Characteristic, Vec<usize>)>,
+    /// Chosen pairs per characteristic
+    pub pairs: Vec<usize>,
+    pub results: Vec<DifferentialAttackResult>,
+}
+
+/// Attacks ‘rounds’ round DES ‘trials’ times with different random keys,
+/// from the ‘seed’. ‘pairs’ chosen pairs are used per characteristic,
+/// or enough for its probability, if not given
+/// @returns characteristics and the outcome of every trial
+pub fn run(rounds: usize, pairs: Option<usize>, trials: usize, seed: u64) -> DifferentialAttackReport {
+    assert!((MIN_ROUNDS..=MAX_ROUNDS).contains(&rounds), "Differential attack works on 4 to 6 rounds");
+    let characteristics = choose_characteristics(&des_sboxes(), rounds);
+    let pairs: Vec<usize> = characteristics.iter()
+        .map(|(c, _)| pairs.unwrap_or_else(|| default_pairs(c))).collect();
+    let mut random = Random::new(seed);
+    let results = (0..trials).map(|_| {
+        let key = random.next_u64();
+        attack(rounds, key, &characteristics, &pairs, &mut random)
+    }).collect();
+    DifferentialAttackReport { rounds, characteristics, pairs, results }
+}
+
+impl DifferentialAttackReport {
+    pub fn successes(&self) -> usize {
+        self.results.iter().filter(|r| r.is_correct).count()
+    }
+
+    /// Human readable report
+    /// @returns text with characteristics and outcome of every trial
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Differential cryptanalysis of {} round DES (Biham, Shamir)\n\n",
+                               self.rounds);
+        for ((characteristic, targets), pairs) in self.characteristics.iter().zip(self.pairs.iter()) {
+            let targets: Vec<String> = targets.iter().map(|s| format!("S{}", s + 1)).collect();
+            text += &format!(
+                "{} round characteristic, recovers bits of K{} entering {}\n  \
+                 trail: {}\n  probability: {:.3e} (1/{:.0})\n  \
+                 difference: in {:08X} {:08X}, out {:08X} {:08X}\n  chosen pairs: {}\n",
+                self.rounds - 3, self.rounds, targets.join(" "), characteristic.describe(),
+                characteristic.probability(), 1.0 / characteristic.probability(),
+                characteristic.input_difference.0, characteristic.input_difference.1,
+                characteristic.output_difference.0, characteristic.output_difference.1, pairs);
+        }
+        text += &format!("\n  {:>18} {:>18} {:>7} {:>7} {:>10}  result\n",
+                         "key", "recovered", "pairs", "right", "candidates");
+        for result in self.results.iter() {
+            text += &format!(
+                "  {:#018X} {:>18} {:>7} {:>7} {:>10}  {}\n",
+                result.key,
+                result.recovered_key.map_or("-".to_string(), |k| format!("{:#018X}", k)),
+                result.pairs, result.right_pairs, result.candidates,
+                if result.is_correct { "recovered" } else { "failed" });
+        }
+        text += &format!("\nKey recovered in {}/{} trials\n", self.successes(), self.results.len());
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_characteristics_cover_all_sboxes() {
+        let sboxes = des_sboxes();
+        for rounds in MIN_ROUNDS..=MAX_ROUNDS {
+            let chosen = choose_characteristics(&sboxes, rounds);
+            let mut covered: Vec<usize> = chosen.iter().flat_map(|(_, t)| t.clone()).collect();
+            covered.sort_unstable();
+            assert_eq!(covered, (0..SBOXES_NUMBER).collect::<Vec<_>>(),
+                       "{} rounds: every S-box should be attacked once", rounds);
+        }
+        // 4 rounds need no luck, 6 rounds are attacked with Biham and
+        // Shamir's 3 round characteristic of probability 1/16
+        let four = choose_characteristics(&sboxes, 4);
+        assert!(four.iter().all(|(c, _)| c.probability() == 1.0), "4 rounds: probability 1");
+        let six = choose_characteristics(&sboxes, 6);
+        assert_eq!(six[0].0.probability(), 1.0 / 16.0, "6 rounds: probability 1/16");
+    }
+
+    #[test]
+    fn test_characteristic_holds_on_data() {
+        // a characteristic of probability 1 should hold on every pair
+        let characteristic = &choose_characteristics(&des_sboxes(), 4)[0].0;
+        let mut random = Random::new(3);
+        let network = des_network(1);
+        let key = random.next_u64();
+        for _ in 0..100 {
+            let block = random.next_u64();
+            let other = block ^ characteristic.block_difference();
+            let encrypt = |block: u64| INITIAL_PERMUTATION.apply(
+                network.encrypt_block(REVERSE_PERMUTATION.apply(block), key));
+            let (high, low) = output_halves(encrypt(block));
+            let (other_high, other_low) = output_halves(encrypt(other));
+            assert_eq!((high ^ other_high, low ^ other_low), characteristic.output_difference);
+            assert_eq!(input_halves(block ^ other), characteristic.input_difference);
+        }
+    }
+
+    #[test]
+    fn test_sbox_output_difference() {
+        for sbox in 0..SBOXES_NUMBER {
+            for output in 0..SBOX_OUTPUTS as u8 {
+                assert_eq!(sbox_output_difference(sbox_output_mask(sbox, output), sbox), output);
+            }
+        }
+    }
+
+    #[test]
+    fn test_key_recovery() {
+        let report = run(4, None, 2, 11);
+        assert_eq!(report.successes(), 2, "4 round key should be recovered");
+        assert!(report.results.iter().all(|r| r.pairs == report.pairs.iter().sum::<usize>()),
+                "Every trial uses all the chosen pairs");
+        let report = run(5, None, 1, 2);
+        assert_eq!(report.successes(), 1, "5 round key should be recovered");
+        assert!(report.to_text().contains("Key recovered in 1/1 trials"), "Report summary");
+        let too_few = run(5, Some(1), 1, 2);
+        assert!(!too_few.results[0].is_correct, "A single pair isn't enough for 5 rounds");
+    }
+}
@@ -0,0 +1,246 @@
+//! Linear properties of S-boxes. Linear approximation table (LAT) of
+//! an S-box tells, for every input mask a and output mask b, how often
+//! the parity of masked input bits equals the parity of masked output
+//! bits. The further it’s from a half of inputs, the more useful the
+//! approximation is for linear cryptanalysis (e.g. Matsui’s attack on
+//! DES is built on S5 with masks 0x10 -> 0xF, which holds only for 12
+//! inputs out of 64)
+
+use super::{SBox, SBOX_INPUTS, SBOX_OUTPUTS};
+
+/// Number of inputs, for which an approximation holds, minus a half of
+/// inputs (32). Rows are input masks, columns are output masks
+pub type LinearTable = [[i32; SBOX_OUTPUTS]; SBOX_INPUTS];
+
+/// Builds linear approximation table of an S-box
+/// @returns table, where [a][b] is #{x: a·x == b·S(x)} - 32
+pub fn linear_approximation(sbox: &SBox) -> LinearTable {
+    let mut table = [[0; SBOX_OUTPUTS]; SBOX_INPUTS];
+    for (input_mask, row) in table.iter_mut().enumerate() {
+        for (output_mask, entry) in row.iter_mut().enumerate() {
+            let holds = (0..SBOX_INPUTS).filter(|&input| {
+                parity(input & input_mask) == parity(sbox[input] as usize & output_mask)
+            }).count();
+            *entry = holds as i32 - (SBOX_INPUTS / 2) as i32;
+        }
+    }
+    table
+}
+
+fn parity(bits: usize) -> bool {
+    bits.count_ones() % 2 == 1
+}
+
+/// Linear approximation of an S-box: parity of input bits, chosen by
+/// ‘input_mask’, is equal to parity of output bits, chosen by
+/// ‘output_mask’
+#[derive(Clone, Debug, PartialEq)]
+pub struct Approximation {
+    pub sbox: String,
+    pub input_mask: u8,
+    pub output_mask: u8,
+    /// Entry of the linear approximation table
+    pub count: i32,
+}
+
+impl Approximation {
+    /// @returns probability of the approximation minus 1/2
+    pub fn bias(&self) -> f64 {
+        self.count as f64 / SBOX_INPUTS as f64
+    }
+
+    /// @returns probability, that the approximation holds
+    pub fn probability(&self) -> f64 {
+        0.5 + self.bias()
+    }
+}
+
+/// Lists all the approximations of an S-box with nonzero masks and
+/// nonzero bias, the highest absolute bias first (ties are kept in
+/// order of masks)
+/// @returns sorted approximations
+pub fn ranked_approximations(name: &str, table: &LinearTable) -> Vec<Approximation> {
+    let mut approximations = vec![];
+    for (input_mask, row) in table.iter().enumerate().skip(1) {
+        for (output_mask, &count) in row.iter().enumerate().skip(1) {
+            if count != 0 {
+                approximations.push(Approximation {
+                    sbox: name.to_string(),
+                    input_mask: input_mask as u8,
+                    output_mask: output_mask as u8,
+                    count,
+                });
+            }
+        }
+    }
+    approximations.sort_by_key(|a| std::cmp::Reverse(a.count.abs()));
+    approximations
+}
+
+/// Linear properties of a single S-box
+#[derive(Clone, Debug)]
+pub struct SBoxReport {
+    pub name: String,
+    pub table: LinearTable,
+    pub approximations: Vec<Approximation>,
+}
+
+/// Linear properties of a set of S-boxes
+#[derive(Clone, Debug)]
+pub struct LinearReport {
+    pub sboxes: Vec<SBoxReport>,
+    /// Number of the highest bias approximations, that are listed in
+    /// reports
+    pub top: usize,
+}
+
+/// Analyzes a set of S-boxes, they’re named S1, S2, ... in order.
+/// Reports list ‘top’ approximations with the highest bias
+/// @returns report of every S-box
+pub fn analyze(sboxes: &[SBox], top: usize) -> LinearReport {
+    LinearReport {
+        sboxes: sboxes.iter().enumerate().map(|(i, sbox)| {
+            let name = format!("S{}", i + 1);
+            let table = linear_approximation(sbox);
+            SBoxReport {
+                approximations: ranked_approximations(&name, &table),
+                name,
+                table,
+            }
+        }).collect(),
+        top,
+    }
+}
+
+impl LinearReport {
+    /// @returns ‘top’ approximations with the highest bias among all
+    /// S-boxes
+    pub fn best_approximations(&self) -> Vec<&Approximation> {
+        let mut all: Vec<&Approximation> = self.sboxes.iter()
+            .flat_map(|s| s.approximations.iter()).collect();
+        all.sort_by_key(|a| std::cmp::Reverse(a.count.abs()));
+        all.truncate(self.top);
+        all
+    }
+
+    /// Human readable report
+    /// @returns text with the best approximations and a table per S-box
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Highest bias approximations (of {} S-boxes):\n",
+                               self.sboxes.len());
+        for approximation in self.best_approximations() {
+            text += &format_approximation(approximation);
+        }
+        text += "\n";
+        for sbox in self.sboxes.iter() {
+            text += &format!("{}:\n", sbox.name);
+            for approximation in sbox.approximations.iter().take(self.top) {
+                text += &format_approximation(approximation);
+            }
+            text += "\n  a\\b  ";
+            for output_mask in 0..SBOX_OUTPUTS {
+                text += &format!("{:>4X}", output_mask);
+            }
+            text += "\n";
+            for (input_mask, row) in sbox.table.iter().enumerate() {
+                text += &format!("  {:#04X} ", input_mask);
+                for count in row.iter() {
+                    text += &format!("{:>4}", count);
+                }
+                text += "\n";
+            }
+            text += "\n";
+        }
+        text
+    }
+
+    /// Machine readable list of approximations of every S-box, the
+    /// highest bias first
+    /// @returns CSV with a header
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("sbox,input_mask,output_mask,count,bias,probability\n");
+        for sbox in self.sboxes.iter() {
+            for a in sbox.approximations.iter() {
+                csv += &format!("{},{},{},{},{:.6},{:.6}\n", a.sbox, a.input_mask,
+                                a.output_mask, a.count, a.bias(), a.probability());
+            }
+        }
+        csv
+    }
+
+    /// Machine readable report
+    /// @returns JSON object with tables and the best approximations
+    /// of every S-box
+    pub fn to_json(&self) -> String {
+        let to_json = |a: &Approximation| format!(
+            "{{\"sbox\": \"{}\", \"input_mask\": {}, \"output_mask\": {}, \
+             \"count\": {}, \"bias\": {:.6}}}",
+            a.sbox, a.input_mask, a.output_mask, a.count, a.bias());
+        let best: Vec<String> = self.best_approximations().into_iter().map(to_json).collect();
+        let sboxes: Vec<String> = self.sboxes.iter().map(|sbox| {
+            let rows: Vec<String> = sbox.table.iter().map(|row| {
+                let counts: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+                format!("[{}]", counts.join(", "))
+            }).collect();
+            let top: Vec<String> = sbox.approximations.iter().take(self.top)
+                .map(to_json).collect();
+            format!(
+                "    {{\"name\": \"{}\",\n     \"best\": [\n      {}\n     ],\n     \
+                 \"lat\": [\n      {}\n     ]}}",
+                sbox.name, top.join(",\n      "), rows.join(",\n      "))
+        }).collect();
+        format!("{{\n  \"best\": [\n    {}\n  ],\n  \"sboxes\": [\n{}\n  ]\n}}\n",
+                best.join(",\n    "), sboxes.join(",\n"))
+    }
+}
+
+fn format_approximation(a: &Approximation) -> String {
+    format!("  {} {:#04X} -> {:#03X}: holds for {:>2}/{}, bias {:+.4}\n",
+            a.sbox, a.input_mask, a.output_mask,
+            a.count + (SBOX_INPUTS / 2) as i32, SBOX_INPUTS, a.bias())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::des_sboxes;
+
+    #[test]
+    fn test_des_linear_tables() {
+        for (i, sbox) in des_sboxes().iter().enumerate() {
+            let table = linear_approximation(sbox);
+            assert_eq!(table[0][0], 32, "S{}: empty masks always hold", i + 1);
+            assert!(table[0].iter().skip(1).all(|&c| c == 0),
+                    "S{}: output bits should be balanced", i + 1);
+        }
+    }
+
+    #[test]
+    fn test_matsui_s5_approximation() {
+        let report = analyze(&des_sboxes(), 10);
+        let best = report.best_approximations()[0];
+        assert_eq!((best.sbox.as_str(), best.input_mask, best.output_mask, best.count),
+                   ("S5", 0x10, 0xF, -20),
+                   "Matsui's S5 approximation should have the highest bias");
+        assert_eq!(best.probability(), 12.0 / 64.0, "It holds for 12 inputs of 64");
+        assert_eq!(report.to_csv().lines().nth(1).map(|l| l.starts_with("S1,")), Some(true),
+                   "CSV lists approximations after the header");
+        assert!(report.to_json().contains(
+            "{\"sbox\": \"S5\", \"input_mask\": 16, \"output_mask\": 15, \"count\": -20"),
+            "JSON should list Matsui's approximation");
+    }
+
+    #[test]
+    fn test_custom_sbox() {
+        // output is the inner bits of the input: every single bit is
+        // exactly linear
+        let mut linear = [0; SBOX_INPUTS];
+        for (input, output) in linear.iter_mut().enumerate() {
+            *output = ((input >> 1) & 0xF) as u8;
+        }
+        let report = analyze(&[linear], 4);
+        let best = report.best_approximations();
+        assert_eq!(best.len(), 4, "Only 'top' approximations are listed");
+        assert!(best.iter().all(|a| a.count == 32), "Linear S-box has bias 1/2");
+    }
+}
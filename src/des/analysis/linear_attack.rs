@@ -0,0 +1,564 @@
+//! Matsui’s linear cryptanalysis of reduced round DES, for training
+//! purposes. Linear trails are searched over the engine’s own S-boxes,
+//! expansion and permutation (a single S-box is approximated per
+//! round, biases are combined by the piling-up lemma). Then known
+//! plaintext / ciphertext pairs are generated under random keys and
+//! - Algorithm 1 recovers a parity bit of round keys from an
+//!   approximation over all the rounds
+//! - Algorithm 2 recovers 6 bits of the last round key (those, which
+//!   enter a single S-box) and a parity bit of the other round keys,
+//!   from an approximation over all the rounds but the last one
+//!
+//! Success rate is measured for several numbers of pairs, so that it’s
+//! seen how many pairs the attacks need
+
+use std::collections::{HashMap, HashSet};
+use super::{des_sboxes, linear, Random, SBox, SBOXES_NUMBER, SBOX_INPUTS, SBOX_OUTPUTS};
+use super::{input_halves, output_halves, sbox_input, sbox_input_shift, sbox_output_mask};
+use crate::des::details::{des_network, Key, EXPANSION, INITIAL_PERMUTATION};
+
+const BITS_IN_HALF: u32 = 32;
+const SBOX_INPUT_MASK: u64 = (1 << 6) - 1;
+pub const MAX_ROUNDS: usize = 8;
+
+/// Approximation of the round function by a single S-box: parity of
+/// masked S-box input equals parity of masked S-box output with
+/// probability 1/2 + count/64
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RoundApproximation {
+    /// Index of S-box (0 for S1)
+    pub sbox: usize,
+    pub input_mask: u8,
+    pub output_mask: u8,
+    /// Entry of the S-box’s linear approximation table
+    pub count: i32,
+}
+
+impl RoundApproximation {
+    /// @returns mask of the round key (48 bit)
+    pub fn key_mask(&self) -> u64 {
+        (self.input_mask as u64) << sbox_input_shift(self.sbox)
+    }
+
+    /// @returns mask of the round function’s input (32 bit half)
+    pub fn input_data_mask(&self) -> u32 {
+        expansion_input_mask(self.key_mask())
+    }
+
+    /// @returns mask of the round function’s output (32 bit half)
+    pub fn output_data_mask(&self) -> u32 {
+        sbox_output_mask(self.sbox, self.output_mask)
+    }
+
+    /// @returns 2 * bias of the approximation
+    pub fn correlation(&self) -> f64 {
+        2.0 * self.count as f64 / SBOX_INPUTS as f64
+    }
+}
+
+/// Linear approximation of several rounds
+#[derive(Clone, Debug)]
+pub struct LinearTrail {
+    /// Approximation of every round, None for rounds, where the round
+    /// function’s output isn’t involved
+    pub rounds: Vec<Option<RoundApproximation>>,
+    /// Masks of the high and low halves before the first round
+    pub input_masks: (u32, u32),
+    /// Masks of the high and low halves after the last round
+    pub output_masks: (u32, u32),
+}
+
+impl LinearTrail {
+    /// @returns probability of the trail minus 1/2
+    pub fn bias(&self) -> f64 {
+        0.5 * self.rounds.iter().flatten().map(|a| a.correlation()).product::<f64>()
+    }
+
+    /// @returns parity of round key bits, that the trail approximates
+    pub fn key_parity(&self, round_keys: &[Key]) -> bool {
+        self.rounds.iter().zip(round_keys.iter())
+            .filter_map(|(approximation, key)| approximation.map(|a| a.key_mask() & key.value))
+            .fold(false, |parity, bits| parity ^ parity_of(bits))
+    }
+
+    /// @returns parity of masked halves of data before and after the
+    /// trail’s rounds
+    pub fn data_parity(&self, (high, low): (u32, u32), (out_high, out_low): (u32, u32)) -> bool {
+        parity_of((self.input_masks.0 & high) as u64)
+            ^ parity_of((self.input_masks.1 & low) as u64)
+            ^ parity_of((self.output_masks.0 & out_high) as u64)
+            ^ parity_of((self.output_masks.1 & out_low) as u64)
+    }
+
+    /// @returns description of the trail, a round per item (‘-’ for
+    /// rounds, that aren’t approximated)
+    pub fn describe(&self) -> String {
+        let rounds: Vec<String> = self.rounds.iter().map(|approximation| match approximation {
+            Some(a) => format!("S{}({:#04X}->{:#03X})", a.sbox + 1, a.input_mask, a.output_mask),
+            None => "-".to_string(),
+        }).collect();
+        rounds.join(" ")
+    }
+}
+
+/// @returns S-box and its output mask, which give the round function’s
+/// output ‘mask’, None if the mask involves several S-boxes
+fn single_sbox_of(mask: u32) -> Option<(usize, u8)> {
+    (0..SBOXES_NUMBER).flat_map(|sbox| (1..SBOX_OUTPUTS as u8).map(move |m| (sbox, m)))
+        .find(|&(sbox, output_mask)| sbox_output_mask(sbox, output_mask) == mask)
+}
+
+/// Checks, that parity of masked S-box output tells apart every key
+/// XORed to the input: no two keys give the same or the complementary
+/// parities over all inputs
+fn distinguishes_keys(sbox: &SBox, output_mask: u8) -> bool {
+    let mut seen = HashSet::new();
+    (0..SBOX_INPUTS).all(|key| {
+        let parities = (0..SBOX_INPUTS).fold(0u64, |parities, input| {
+            parities | (parity_of((sbox[input ^ key] & output_mask) as u64) as u64) << input
+        });
+        // complementary parities are the same guess with flipped bias
+        seen.insert(parities.min(!parities))
+    })
+}
+
+/// @returns mask of a 32 bit half, which gives the same parity as
+/// ‘expanded_mask’ applied to the expanded half
+fn expansion_input_mask(expanded_mask: u64) -> u32 {
+    (0..BITS_IN_HALF).filter(|&bit| parity_of(EXPANSION.apply(1 << bit) & expanded_mask))
+        .fold(0, |mask, bit| mask | 1 << bit)
+}
+
+fn parity_of(bits: u64) -> bool {
+    bits.count_ones() % 2 == 1
+}
+
+/// Branch and bound search of the trail with the highest bias, where
+/// every round has at most one active S-box. As in Matsui’s search,
+/// the best correlations of shorter trails bound what the remaining
+/// rounds can add
+struct TrailSearch {
+    rounds: usize,
+    /// Best absolute correlation of a trail over [i] rounds, for every
+    /// i less than ‘rounds’
+    bounds: Vec<f64>,
+    /// Last round’s output mask should involve a single S-box (so that
+    /// the trail can be used by Algorithm 2)
+    single_sbox_output: bool,
+    /// Round function’s output masks, which involve a single S-box
+    single_sbox_masks: HashMap<u32, (usize, u8)>,
+    /// Masks of a single S-box, for which every guess of the S-box’s
+    /// key bits gives a different bias (so Algorithm 2 can tell them)
+    distinguishing_masks: HashSet<u32>,
+    /// Output masks, a round may freely choose: zero or any mask of a
+    /// single S-box (sorted, so that the search is reproducible)
+    free_masks: Vec<u32>,
+    /// For every S-box and output mask, input masks with nonzero
+    /// LAT entries, the highest bias first
+    candidates: Vec<Vec<Vec<(u8, i32)>>>,
+    best_correlation: f64,
+    best: Option<LinearTrail>,
+    /// Round function’s output masks of the current path (b1, b2, ...)
+    masks: Vec<u32>,
+    approximations: Vec<Option<RoundApproximation>>,
+}
+
+impl TrailSearch {
+    fn new(sboxes: &[SBox], rounds: usize, single_sbox_output: bool, bounds: Vec<f64>) -> Self {
+        let mut single_sbox_masks = HashMap::new();
+        let mut distinguishing_masks = HashSet::new();
+        let mut candidates = vec![];
+        for (sbox, table) in sboxes.iter().enumerate() {
+            let lookup = table;
+            let table = linear::linear_approximation(table);
+            let mut by_output = vec![vec![]; SBOX_OUTPUTS];
+            for output_mask in 1..SBOX_OUTPUTS {
+                let mask = sbox_output_mask(sbox, output_mask as u8);
+                single_sbox_masks.insert(mask, (sbox, output_mask as u8));
+                if distinguishes_keys(lookup, output_mask as u8) {
+                    distinguishing_masks.insert(mask);
+                }
+                for (input_mask, row) in table.iter().enumerate().skip(1) {
+                    if row[output_mask] != 0 {
+                        by_output[output_mask].push((input_mask as u8, row[output_mask]));
+                    }
+                }
+                by_output[output_mask].sort_by_key(|&(_, count): &(u8, i32)| -count.abs());
+            }
+            candidates.push(by_output);
+        }
+        let mut free_masks: Vec<u32> = single_sbox_masks.keys().cloned().collect();
+        free_masks.push(0);
+        free_masks.sort_unstable();
+        Self {
+            rounds,
+            bounds,
+            single_sbox_output,
+            single_sbox_masks,
+            distinguishing_masks,
+            free_masks,
+            candidates,
+            best_correlation: 0.0,
+            best: None,
+            masks: vec![],
+            approximations: vec![],
+        }
+    }
+
+    /// @returns the found trail, None if no trail satisfies the
+    /// constraints
+    fn run(mut self) -> Option<LinearTrail> {
+        for first_mask in self.free_masks.clone() {
+            self.search(1, 0, first_mask, 1.0);
+        }
+        self.best
+    }
+
+    /// Round ‘round’ has to approximate round function’s output by
+    /// ‘mask’. The next round’s mask is forced by the Feistel
+    /// structure (previous mask XOR this round’s input mask), besides
+    /// after the first round, where it’s free
+    fn search(&mut self, round: usize, previous_mask: u32, mask: u32, correlation: f64) {
+        if round > self.rounds {
+            self.finish(mask, correlation);
+            return;
+        }
+        let options: Vec<Option<RoundApproximation>> = if mask == 0 {
+            vec![None]
+        } else if let Some(&(sbox, output_mask)) = self.single_sbox_masks.get(&mask) {
+            self.candidates[sbox][output_mask as usize].iter()
+                .map(|&(input_mask, count)| Some(RoundApproximation {
+                    sbox, input_mask, output_mask, count,
+                })).collect()
+        } else {
+            return;
+        };
+        self.masks.push(mask);
+        for approximation in options {
+            let correlation = correlation * approximation.map_or(1.0, |a| a.correlation());
+            // candidates are sorted, the rest are even worse
+            if correlation.abs() * self.bounds[self.rounds - round] <= self.best_correlation {
+                break;
+            }
+            self.approximations.push(approximation);
+            let input_mask = approximation.map_or(0, |a| a.input_data_mask());
+            if round == 1 {
+                for next_mask in self.free_masks.clone() {
+                    self.search(round + 1, mask, next_mask, correlation);
+                }
+            } else {
+                self.search(round + 1, mask, previous_mask ^ input_mask, correlation);
+            }
+            self.approximations.pop();
+        }
+        self.masks.pop();
+    }
+
+    /// Path is complete, ‘last_mask’ is the mask of the high half after
+    /// the last round
+    fn finish(&mut self, last_mask: u32, correlation: f64) {
+        if self.approximations.iter().all(|a| a.is_none()) {
+            return;
+        }
+        if self.single_sbox_output && !self.distinguishing_masks.contains(&last_mask) {
+            return;
+        }
+        let first_input = self.approximations[0].map_or(0, |a| a.input_data_mask());
+        let second_mask = self.masks.get(1).cloned().unwrap_or(last_mask);
+        self.best_correlation = correlation.abs();
+        self.best = Some(LinearTrail {
+            rounds: self.approximations.clone(),
+            input_masks: (self.masks[0], second_mask ^ first_input),
+            output_masks: (last_mask, self.masks[self.rounds - 1]),
+        });
+    }
+}
+
+/// Finds the linear trail over ‘rounds’ rounds with the highest bias.
+/// If ‘single_sbox_output’ is set, the high half after the last round
+/// is masked by the output of a single S-box, that tells apart all
+/// its key guesses
+/// @returns the best trail, None if there is no such trail
+pub fn best_trail(sboxes: &[SBox], rounds: usize, single_sbox_output: bool) -> Option<LinearTrail> {
+    let mut bounds = vec![1.0];
+    for shorter in 1..rounds {
+        let trail = TrailSearch::new(sboxes, shorter, false, bounds.clone()).run();
+        bounds.push(trail.map_or(0.0, |t| 2.0 * t.bias().abs()));
+    }
+    TrailSearch::new(sboxes, rounds, single_sbox_output, bounds).run()
+}
+
+/// Known plaintexts and ciphertexts, both taken without the initial
+/// and the reverse permutation, i.e. as halves entering the first
+/// round and leaving the last one
+struct KnownPairs {
+    round_keys: Vec<Key>,
+    pairs: Vec<(u64, u64)>,
+}
+
+impl KnownPairs {
+    fn generate(rounds: usize, count: usize, random: &mut Random) -> Self {
+        let network = des_network(rounds);
+        let round_keys = network.encrypting_keys(random.next_u64());
+        let pairs = (0..count).map(|_| {
+            let plaintext = random.next_u64();
+            let ciphertext = network.act_on_block(plaintext, &mut round_keys.iter().cloned());
+            // initial permutation is the inverse of the reverse one
+            (INITIAL_PERMUTATION.apply(plaintext), INITIAL_PERMUTATION.apply(ciphertext))
+        }).collect();
+        Self { round_keys, pairs }
+    }
+}
+
+/// Guesses parity of key bits, given how many times the data side of
+/// an approximation was zero
+fn guess_parity(zeros: usize, pairs: usize, bias: f64) -> bool {
+    (2 * zeros > pairs) == (bias < 0.0)
+}
+
+/// Algorithm 1: counts parity of the data side of an approximation
+/// over all the rounds
+/// @returns guessed parity of round keys for every number of pairs in
+/// ‘checkpoints’ (sorted)
+fn algorithm_1(trail: &LinearTrail, pairs: &[(u64, u64)], checkpoints: &[usize]) -> Vec<bool> {
+    let mut zeros = 0;
+    let mut guesses = vec![];
+    let mut checkpoints = checkpoints.iter().peekable();
+    for (i, &(plaintext, ciphertext)) in pairs.iter().enumerate() {
+        if !trail.data_parity(input_halves(plaintext), output_halves(ciphertext)) {
+            zeros += 1;
+        }
+        while checkpoints.peek() == Some(&&(i + 1)) {
+            guesses.push(guess_parity(zeros, i + 1, trail.bias()));
+            checkpoints.next();
+        }
+    }
+    guesses
+}
+
+/// Algorithm 2: partially decrypts the last round with every guess of
+/// 6 key bits of a single S-box, the guess that gives the highest bias
+/// of the approximation over the other rounds wins
+/// @returns guessed key bits and parity of round keys for every number
+/// of pairs in ‘checkpoints’ (sorted)
+fn algorithm_2(trail: &LinearTrail, sbox: &SBox, sbox_index: usize, output_mask: u8,
+               pairs: &[(u64, u64)], checkpoints: &[usize]) -> Vec<(u8, bool)> {
+    // Only the S-box input and parity of the rest matter, so pairs are
+    // counted by them, then every guess is checked on the counts
+    let mut counts = [[0usize; 2]; SBOX_INPUTS];
+    let mut guesses = vec![];
+    let mut checkpoints = checkpoints.iter().peekable();
+    for (i, &(plaintext, ciphertext)) in pairs.iter().enumerate() {
+        let (high, low) = output_halves(ciphertext);
+        // before the last round: low half is the current high one,
+        // high half is current low one XOR round function
+        let data_parity = trail.data_parity(input_halves(plaintext), (low, high));
+        counts[sbox_input(high, sbox_index) as usize][data_parity as usize] += 1;
+        while checkpoints.peek() == Some(&&(i + 1)) {
+            guesses.push(best_guess(&counts, sbox, output_mask, i + 1, trail.bias()));
+            checkpoints.next();
+        }
+    }
+    guesses
+}
+
+fn best_guess(counts: &[[usize; 2]; SBOX_INPUTS], sbox: &SBox, output_mask: u8,
+              pairs: usize, bias: f64) -> (u8, bool) {
+    let mut best = (0, false);
+    let mut best_deviation = -1;
+    for key in 0..SBOX_INPUTS {
+        let zeros: usize = counts.iter().enumerate().map(|(input, count)| {
+            let output_parity = parity_of((sbox[input ^ key] & output_mask) as u64);
+            count[output_parity as usize]
+        }).sum();
+        let deviation = (2 * zeros as i64 - pairs as i64).abs();
+        if deviation > best_deviation {
+            best_deviation = deviation;
+            best = (key as u8, guess_parity(zeros, pairs, bias));
+        }
+    }
+    best
+}
+
+/// Numbers of pairs, that show how success rate grows: powers of 4
+/// from 64 up to Matsui’s estimate of 8 / bias^2 pairs (for 97.7%
+/// success) of Algorithm 2, or of Algorithm 1 on a single round
+/// @returns sorted numbers of pairs
+pub fn default_pair_counts(rounds: usize) -> Vec<usize> {
+    let sboxes = des_sboxes();
+    let trail = if rounds > 1 {
+        best_trail(&sboxes, rounds - 1, true)
+    } else {
+        best_trail(&sboxes, rounds, false)
+    };
+    let bias = trail.map_or(0.5, |t| t.bias().abs());
+    let limit = (8.0 / (bias * bias)) as usize;
+    let mut counts = vec![64];
+    while *counts.last().unwrap() < limit {
+        counts.push(counts.last().unwrap() * 4);
+    }
+    counts
+}
+
+/// Number of successful key recoveries out of all trials, for a given
+/// number of known pairs
+#[derive(Clone, Debug)]
+pub struct SuccessRate {
+    pub pairs: usize,
+    pub trials: usize,
+    pub successes: usize,
+}
+
+impl SuccessRate {
+    pub fn rate(&self) -> f64 {
+        self.successes as f64 / self.trials.max(1) as f64
+    }
+}
+
+/// Results of both algorithms
+#[derive(Clone, Debug)]
+pub struct LinearAttackReport {
+    pub rounds: usize,
+    pub algorithm_1_trail: LinearTrail,
+    pub algorithm_1: Vec<SuccessRate>,
+    /// There is no Algorithm 2 on a single round
+    pub algorithm_2_trail: Option<LinearTrail>,
+    pub algorithm_2: Vec<SuccessRate>,
+}
+
+/// Runs both algorithms on DES, reduced to ‘rounds’ rounds (up to 8),
+/// ‘trials’ times with different random keys, from the ‘seed’. In each
+/// trial, success is checked for every number of pairs in ‘pair_counts’
+/// @returns success rates of both algorithms
+pub fn run(rounds: usize, pair_counts: &[usize], trials: usize, seed: u64) -> LinearAttackReport {
+    assert!((1..=MAX_ROUNDS).contains(&rounds), "Linear attack works on 1 to 8 rounds");
+    let sboxes = des_sboxes();
+    let mut checkpoints = pair_counts.to_vec();
+    checkpoints.sort_unstable();
+    checkpoints.dedup();
+    let max_pairs = checkpoints.last().cloned().unwrap_or(0);
+
+    let trail_1 = best_trail(&sboxes, rounds, false).expect("Any number of rounds has a trail");
+    let trail_2 = if rounds > 1 { best_trail(&sboxes, rounds - 1, true) } else { None };
+    let mut successes_1 = vec![0; checkpoints.len()];
+    let mut successes_2 = vec![0; checkpoints.len()];
+    let mut random = Random::new(seed);
+    for _ in 0..trials {
+        let known = KnownPairs::generate(rounds, max_pairs, &mut random);
+        let parity = trail_1.key_parity(&known.round_keys);
+        for (success, guess) in successes_1.iter_mut()
+            .zip(algorithm_1(&trail_1, &known.pairs, &checkpoints)) {
+            *success += (guess == parity) as usize;
+        }
+        if let Some(trail_2) = &trail_2 {
+            let (sbox, output_mask) = single_sbox_of(trail_2.output_masks.0)
+                .expect("Algorithm 2 trail ends on a single S-box");
+            let last_key = known.round_keys[rounds - 1].value;
+            let key_bits = ((last_key >> sbox_input_shift(sbox)) & SBOX_INPUT_MASK) as u8;
+            let parity = trail_2.key_parity(&known.round_keys);
+            let guesses = algorithm_2(trail_2, &sboxes[sbox], sbox, output_mask,
+                                      &known.pairs, &checkpoints);
+            for (success, guess) in successes_2.iter_mut().zip(guesses) {
+                *success += (guess == (key_bits, parity)) as usize;
+            }
+        }
+    }
+    let rates = |successes: Vec<usize>| checkpoints.iter().zip(successes)
+        .map(|(&pairs, successes)| SuccessRate { pairs, trials, successes }).collect();
+    LinearAttackReport {
+        rounds,
+        algorithm_1_trail: trail_1,
+        algorithm_1: rates(successes_1),
+        algorithm_2: if trail_2.is_some() { rates(successes_2) } else { vec![] },
+        algorithm_2_trail: trail_2,
+    }
+}
+
+impl LinearAttackReport {
+    /// Human readable report
+    /// @returns text with trails and success rates of both algorithms
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Linear cryptanalysis of {} round DES (Matsui)\n\n", self.rounds);
+        text += &format!("Algorithm 1: {} round approximation, recovers 1 key parity bit\n",
+                         self.rounds);
+        text += &describe_trail(&self.algorithm_1_trail);
+        text += &describe_rates(&self.algorithm_1);
+        if let Some(trail) = &self.algorithm_2_trail {
+            let (sbox, _) = single_sbox_of(trail.output_masks.0).unwrap_or((0, 0));
+            text += &format!(
+                "\nAlgorithm 2: {} round approximation, recovers 6 bits of K{} \
+                 (entering S{}) and 1 key parity bit\n",
+                self.rounds - 1, self.rounds, sbox + 1);
+            text += &describe_trail(trail);
+            text += &describe_rates(&self.algorithm_2);
+        }
+        text
+    }
+}
+
+fn describe_trail(trail: &LinearTrail) -> String {
+    format!("  trail: {}\n  bias: {:+.3e} (1/bias^2 = {:.0})\n  \
+             masks: in {:08X} {:08X}, out {:08X} {:08X}\n",
+            trail.describe(), trail.bias(), 1.0 / (trail.bias() * trail.bias()),
+            trail.input_masks.0, trail.input_masks.1,
+            trail.output_masks.0, trail.output_masks.1)
+}
+
+fn describe_rates(rates: &[SuccessRate]) -> String {
+    let mut text = format!("  {:>10} {:>12}\n", "pairs", "success");
+    for rate in rates.iter() {
+        text += &format!("  {:>10} {:>5}/{:<4} {:>5.1}%\n", rate.pairs, rate.successes,
+                         rate.trials, rate.rate() * 100.0);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_trails() {
+        let sboxes = des_sboxes();
+        let one = best_trail(&sboxes, 1, false).unwrap();
+        assert_eq!(one.bias(), -20.0 / 64.0, "Single round: S5 approximation of Matsui");
+        assert_eq!(one.rounds[0].map(|a| (a.sbox, a.input_mask, a.output_mask)),
+                   Some((4, 0x10, 0xF)), "Single round: S5 0x10 -> 0xF");
+        // Matsui's 3 round approximation holds with probability 0.70
+        let three = best_trail(&sboxes, 3, false).unwrap();
+        assert!((three.bias() - 2.0 * (20.0f64 / 64.0).powi(2)).abs() < 1e-12,
+                "Three rounds should have bias 2 * (20/64)^2, got {}", three.bias());
+    }
+
+    #[test]
+    fn test_trail_holds_on_data() {
+        // trail should hold exactly as often as its bias says, up to
+        // statistical noise
+        let sboxes = des_sboxes();
+        let trail = best_trail(&sboxes, 3, false).unwrap();
+        let known = KnownPairs::generate(3, 20000, &mut Random::new(7));
+        let parity = trail.key_parity(&known.round_keys);
+        let holds = known.pairs.iter().filter(|&&(p, c)| {
+            trail.data_parity(input_halves(p), output_halves(c)) == parity
+        }).count();
+        let probability = holds as f64 / known.pairs.len() as f64;
+        assert!((probability - 0.5 - trail.bias()).abs() < 0.02,
+                "Trail holds with probability {}, but its bias is {}", probability, trail.bias());
+    }
+
+    #[test]
+    fn test_key_recovery() {
+        for rounds in 2..4 {
+            let report = run(rounds, &[2000], 3, 5);
+            assert_eq!(report.algorithm_2[0].successes, 3,
+                       "Algorithm 2 should recover last round key bits on {} rounds", rounds);
+        }
+        let report = run(4, &[16, 4000], 4, 1);
+        assert_eq!(report.algorithm_1.len(), 2, "Rate per number of pairs");
+        assert_eq!(report.algorithm_1[1].successes, 4,
+                   "Algorithm 1 should recover the parity on 4 rounds");
+        assert_eq!(report.algorithm_2[1].successes, 4,
+                   "Algorithm 2 should recover last round key bits on 4 rounds");
+        assert!(report.to_text().contains("Algorithm 2"), "Report misses Algorithm 2");
+    }
+}
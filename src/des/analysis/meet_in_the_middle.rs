@@ -0,0 +1,215 @@
+//! Meet-in-the-middle attack on double DES, E(K2, E(K1, P)), for
+//! training purposes. Every key is limited to 2^n values (n of its bits
+//! are unknown). All the encryptions of a known plaintext by K1 are put
+//! into a hash table, then the ciphertext is decrypted by every K2, and
+//! a hit in the table suggests a pair of keys. Other known pairs tell
+//! the right pair of keys from false alarms. So the attack costs about
+//! 2 * 2^n encryptions and 2^n table entries, instead of 2^(2n)
+//! encryptions of the brute force, that’s why 2DES is barely stronger
+//! than DES
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use super::Random;
+use crate::des::crack::{KeySpace, PARITY_BITS};
+use crate::des::details::{des_network, DES_ROUNDS_NUMBER};
+
+const BITS_IN_KEY: u32 = 64;
+/// Larger tables don’t fit in memory of a usual machine
+pub const MAX_BITS: u32 = 24;
+pub const DEFAULT_BITS: [u32; 4] = [8, 12, 16, 20];
+pub const DEFAULT_PAIRS: usize = 2;
+
+/// @returns 2DES encryption of a block
+pub fn double_encrypt_block(data: u64, (key1, key2): (u64, u64)) -> u64 {
+    let network = des_network(DES_ROUNDS_NUMBER);
+    network.encrypt_block(network.encrypt_block(data, key1), key2)
+}
+
+/// @returns key space, where the ‘bits’ lowest key bits (parity bits
+/// aside) are unknown, and the others are taken from ‘known’
+pub fn limited_space(known: u64, bits: u32) -> KeySpace {
+    let unknown = (0..BITS_IN_KEY).filter(|bit| (PARITY_BITS >> bit) & 1 == 0)
+        .take(bits as usize)
+        .fold(0, |unknown, bit| unknown | (1 << bit));
+    KeySpace::new(known, unknown)
+}
+
+/// Outcome of a single attack
+#[derive(Clone, Debug)]
+pub struct MitmResult {
+    pub keys: (u64, u64),
+    pub recovered_keys: Option<(u64, u64)>,
+    pub table_entries: usize,
+    /// Estimated memory of the hash table, in bytes
+    pub table_bytes: usize,
+    /// Key pairs, which matched the first known pair, but not others
+    pub false_alarms: usize,
+    pub encryptions: u64,
+    pub build_time: Duration,
+    pub match_time: Duration,
+}
+
+/// Recovers both keys of 2DES, each is known to be in its key space,
+/// from the known pairs of plaintext and ciphertext
+/// @returns keys and the cost of the attack
+fn attack(spaces: (KeySpace, KeySpace), pairs: &[(u64, u64)], keys: (u64, u64)) -> MitmResult {
+    let network = des_network(DES_ROUNDS_NUMBER);
+    let (plaintext, ciphertext) = pairs[0];
+    let start = Instant::now();
+    let size = 1u64 << spaces.0.unknown_bits();
+    let mut table = HashMap::with_capacity(size as usize);
+    for index in 0..size {
+        let key1 = spaces.0.key(index);
+        table.insert(network.encrypt_block(plaintext, key1), key1);
+    }
+    let build_time = start.elapsed();
+    let start = Instant::now();
+    let mut encryptions = size;
+    let mut false_alarms = 0;
+    let mut recovered_keys = None;
+    for index in 0..1u64 << spaces.1.unknown_bits() {
+        let key2 = spaces.1.key(index);
+        encryptions += 1;
+        if let Some(&key1) = table.get(&network.decrypt_block(ciphertext, key2)) {
+            encryptions += 2 * (pairs.len() - 1) as u64;
+            if pairs[1..].iter().all(|&(p, c)| double_encrypt_block(p, (key1, key2)) == c) {
+                recovered_keys = Some((key1, key2));
+                break;
+            }
+            false_alarms += 1;
+        }
+    }
+    // a bucket of the table holds a key, a value, and a control byte
+    let table_bytes = table.capacity() * (2 * std::mem::size_of::<u64>() + 1);
+    MitmResult {
+        keys,
+        recovered_keys,
+        table_entries: table.len(),
+        table_bytes,
+        false_alarms,
+        encryptions,
+        build_time,
+        match_time: start.elapsed(),
+    }
+}
+
+/// Results of attacks on keys limited to 2^bits values each
+#[derive(Clone, Debug)]
+pub struct MitmRun {
+    pub bits: u32,
+    pub results: Vec<MitmResult>,
+}
+
+impl MitmRun {
+    pub fn successes(&self) -> usize {
+        self.results.iter().filter(|r| r.recovered_keys == Some(r.keys)).count()
+    }
+
+    fn average<F: Fn(&MitmResult) -> f64>(&self, value: F) -> f64 {
+        self.results.iter().map(value).sum::<f64>() / self.results.len().max(1) as f64
+    }
+}
+
+/// Results of attacks for several limits of the key space
+#[derive(Clone, Debug)]
+pub struct MitmReport {
+    pub pairs: usize,
+    pub runs: Vec<MitmRun>,
+}
+
+/// Attacks 2DES ‘trials’ times for every number of unknown bits per key
+/// in ‘bits’, with random keys and ‘pairs’ random known pairs (from the
+/// ‘seed’)
+/// @returns results of every attack
+pub fn run(bits: &[u32], pairs: usize, trials: usize, seed: u64) -> MitmReport {
+    assert!(bits.iter().all(|&b| b <= MAX_BITS), "Key space is limited to 2^24 keys");
+    let pairs = pairs.max(1);
+    let mut random = Random::new(seed);
+    let runs = bits.iter().map(|&bits| {
+        let results = (0..trials).map(|_| {
+            let spaces = (limited_space(random.next_u64(), bits),
+                          limited_space(random.next_u64(), bits));
+            let keys = (spaces.0.key(random.next_u64()), spaces.1.key(random.next_u64()));
+            let known: Vec<(u64, u64)> = (0..pairs).map(|_| {
+                let plaintext = random.next_u64();
+                (plaintext, double_encrypt_block(plaintext, keys))
+            }).collect();
+            attack(spaces, &known, keys)
+        }).collect();
+        MitmRun { bits, results }
+    }).collect();
+    MitmReport { pairs, runs }
+}
+
+impl MitmReport {
+    /// Human readable report
+    /// @returns text with the cost of the attack per key space limit
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Meet-in-the-middle attack on 2DES, E(K2, E(K1, P)), {} known pair(s)\n\
+             Every key is limited to 2^n values, brute force would take 2^2n\n\n",
+            self.pairs);
+        text += &format!("  {:>3} {:>10} {:>11} {:>10} {:>10} {:>13} {:>13} {:>7} {:>9}\n",
+                         "n", "entries", "memory", "build", "match", "encryptions",
+                         "brute force", "alarms", "recovered");
+        for run in self.runs.iter() {
+            text += &format!(
+                "  {:>3} {:>10.0} {:>11} {:>8.3} s {:>8.3} s {:>13.0} {:>13} {:>7.1} {:>7}/{}\n",
+                run.bits, run.average(|r| r.table_entries as f64),
+                format_bytes(run.average(|r| r.table_bytes as f64)),
+                run.average(|r| r.build_time.as_secs_f64()),
+                run.average(|r| r.match_time.as_secs_f64()),
+                run.average(|r| r.encryptions as f64),
+                format!("2^{}", 2 * run.bits),
+                run.average(|r| r.false_alarms as f64),
+                run.successes(), run.results.len());
+        }
+        text
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limited_space() {
+        let space = limited_space(!0, 10);
+        assert_eq!(space.unknown_bits(), 10);
+        assert_eq!(space.unknown, 0b1110_1111_1110, "Parity bits are skipped");
+        assert_eq!(space.key(0) & space.unknown, 0);
+    }
+
+    #[test]
+    fn test_recovers_both_keys() {
+        let report = run(&[6, 10], 2, 3, 17);
+        for run in report.runs.iter() {
+            assert_eq!(run.successes(), 3, "Keys of {} bits should be recovered", run.bits);
+            let entries = 1 << run.bits;
+            assert!(run.results.iter().all(|r| r.table_entries == entries),
+                    "Table holds every encryption by the first key");
+            assert!(run.results.iter().all(|r| r.encryptions <= 2 * entries as u64 + 2),
+                    "Attack costs about two encryptions per key");
+        }
+        assert!(report.to_text().contains("2^20"), "Report compares with brute force");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(100.0), "100.0 B");
+        assert_eq!(format_bytes(1536.0), "1.5 KiB");
+        assert_eq!(format_bytes(17.0 * 1024.0 * 1024.0), "17.0 MiB");
+    }
+}
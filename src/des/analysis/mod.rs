@@ -0,0 +1,212 @@
+//! Cryptanalysis of DES and its building blocks. S-boxes
+//! (granulations) are analyzed as plain lookup tables, so the same
+//! code works on the standard ones, taken straight from the engine,
+//! and on any custom set
+
+pub mod differential;
+pub mod differential_attack;
+pub mod linear;
+pub mod linear_attack;
+pub mod meet_in_the_middle;
+
+use std::error;
+use std::fmt;
+use crate::des::details::{EncodingTable, GRANULATIONS, EXPANSION, FINAL_PERMUTATION};
+
+pub const SBOX_INPUTS: usize = 64;
+pub const SBOX_OUTPUTS: usize = 16;
+pub const SBOXES_NUMBER: usize = 8;
+const SBOX_INPUT_BITS: u32 = 6;
+const SBOX_OUTPUT_BITS: u32 = 4;
+const BITS_IN_HALF: u32 = 32;
+
+/// S-box as a lookup table of a 6 bit input (as it comes after
+/// expansion and key mixing, i.e. the row is chosen by the outer bits)
+/// to a 4 bit output
+pub type SBox = [u8; SBOX_INPUTS];
+
+/// S-boxes, the DES engine uses (S1..S8)
+/// @returns lookup tables of the S-boxes
+pub fn des_sboxes() -> [SBox; SBOXES_NUMBER] {
+    let mut sboxes = [[0; SBOX_INPUTS]; SBOXES_NUMBER];
+    for (sbox, granulation) in sboxes.iter_mut().zip(GRANULATIONS.iter()) {
+        *sbox = sbox_of(granulation);
+    }
+    sboxes
+}
+
+/// S-box of an engine’s granulation (e.g. of a cipher definition)
+/// @returns lookup table of the S-box
+pub fn sbox_of(granulation: &EncodingTable) -> SBox {
+    let mut sbox = [0; SBOX_INPUTS];
+    for (input, output) in sbox.iter_mut().enumerate() {
+        *output = granulation.apply(input as u64) as u8;
+    }
+    sbox
+}
+
+/// Builds an S-box from a spec-like table of 4 rows by 16 columns
+/// (the row is chosen by the outer bits of input, the column by the
+/// inner four)
+/// @returns lookup table of the S-box
+pub fn sbox_from_rows(rows: &[[u8; SBOX_OUTPUTS]; 4]) -> SBox {
+    let mut sbox = [0; SBOX_INPUTS];
+    for (input, output) in sbox.iter_mut().enumerate() {
+        let row = ((input >> 4) & 0b10) | (input & 1);
+        let column = (input >> 1) & 0xF;
+        *output = rows[row][column];
+    }
+    sbox
+}
+
+/// @returns shift of an S-box’s 6 bits in the 48 bit expanded half
+/// (and in the round key)
+pub(crate) fn sbox_input_shift(sbox: usize) -> u32 {
+    SBOX_INPUT_BITS * (SBOXES_NUMBER - sbox - 1) as u32
+}
+
+/// @returns 6 bit input of an S-box after expansion of the round
+/// function’s input ‘half’ (before mixing with the round key)
+pub(crate) fn sbox_input(half: u32, sbox: usize) -> u8 {
+    ((EXPANSION.apply(half as u64) >> sbox_input_shift(sbox)) & (SBOX_INPUTS as u64 - 1)) as u8
+}
+
+/// @returns bits of the round function’s output, which corresponds to
+/// the S-box’s output bits ‘mask’ (i.e. the mask after permutation P)
+pub(crate) fn sbox_output_mask(sbox: usize, mask: u8) -> u32 {
+    let shift = SBOX_OUTPUT_BITS * (SBOXES_NUMBER - sbox - 1) as u32;
+    FINAL_PERMUTATION.apply((mask as u64) << shift) as u32
+}
+
+/// @returns (high, low) halves of a block, entering the first round
+/// (i.e. after the initial permutation)
+pub(crate) fn input_halves(block: u64) -> (u32, u32) {
+    ((block >> BITS_IN_HALF) as u32, block as u32)
+}
+
+/// @returns (high, low) halves of a block after the last round (i.e.
+/// before the reverse permutation). The last round doesn’t swap
+/// halves, so they come swapped
+pub(crate) fn output_halves(block: u64) -> (u32, u32) {
+    (block as u32, (block >> BITS_IN_HALF) as u32)
+}
+
+/// Small deterministic pseudo random generator (xorshift64*), so that
+/// experiments can be repeated from a seed
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // zero state would stay zero forever
+        Self { state: seed ^ 0x9E3779B97F4A7C15 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+}
+
+/// Possible ERRORS during parsing of S-boxes from a text, namely:
+/// NotANumber(word)  - a word isn’t a decimal / hex number
+/// BadOutput(value)  - a number doesn’t fit in 4 bits
+/// BadLength(count)  - count of numbers isn’t a positive multiple of 64
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseSBoxError {
+    NotANumber(String),
+    BadOutput(u64),
+    BadLength(usize),
+}
+
+impl fmt::Display for ParseSBoxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            ParseSBoxError::NotANumber(word) =>
+                write!(f, "<S-box entry '{}' isn't a number>", word),
+            ParseSBoxError::BadOutput(value) =>
+                write!(f, "<S-box entry {} doesn't fit in 4 bits>", value),
+            ParseSBoxError::BadLength(count) =>
+                write!(f, "<{} S-box entries given, expected 64 per S-box>", count),
+        }
+    }
+}
+
+impl error::Error for ParseSBoxError {}
+
+/// Parses S-boxes from a text, where each S-box is written as in the
+/// spec: 4 rows of 16 numbers. Numbers are separated with whitespace
+/// or commas, hex numbers start with 0x, everything after ’#’ on a
+/// line is a comment
+/// @returns Result with lookup tables of all the S-boxes in the text
+pub fn parse_sboxes(text: &str) -> Result<Vec<SBox>, ParseSBoxError> {
+    let mut entries = vec![];
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for word in line.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|w| !w.is_empty()) {
+            let value = match word.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => word.parse(),
+            }.map_err(|_| ParseSBoxError::NotANumber(word.to_string()))?;
+            if value >= SBOX_OUTPUTS as u64 {
+                return Err(ParseSBoxError::BadOutput(value));
+            }
+            entries.push(value as u8);
+        }
+    }
+    if entries.is_empty() || entries.len() % SBOX_INPUTS != 0 {
+        return Err(ParseSBoxError::BadLength(entries.len()));
+    }
+    Ok(entries.chunks(SBOX_INPUTS).map(|chunk| {
+        let mut rows = [[0; SBOX_OUTPUTS]; 4];
+        for (row, values) in rows.iter_mut().zip(chunk.chunks(SBOX_OUTPUTS)) {
+            row.copy_from_slice(values);
+        }
+        sbox_from_rows(&rows)
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_des_sboxes() {
+        let sboxes = des_sboxes();
+        // S1, row 0 of the spec table
+        let s1 = sbox_from_rows(&[
+            [14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7],
+            [0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8],
+            [4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0],
+            [15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13],
+        ]);
+        assert_eq!(sboxes[0][..], s1[..], "S1 lookup differs from the spec table");
+        assert_eq!(sboxes[0][0b011000], 5, "S1(011000) of the classic example");
+        assert_eq!(sboxes[7][0b100111], 7, "S8(100111) of the classic example");
+    }
+
+    #[test]
+    fn test_parse_sboxes() {
+        let mut text = String::from("# S1, as in the spec\n");
+        for row in [
+            "14 4 13 1 2 15 11 8 3 10 6 12 5 9 0 7",
+            "0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8",
+            "4 1 14 8 13 6 2 11 15 12 9 7 3 10 5 0",
+            "0xF 0xC 8 2 4 9 1 7 5 11 3 14 10 0 6 13 # hex is fine too",
+        ].iter() {
+            text += row;
+            text += "\n";
+        }
+        let sboxes = parse_sboxes(&text).expect("Valid S-box");
+        assert_eq!(sboxes.len(), 1, "A single S-box is given");
+        assert_eq!(sboxes[0][..], des_sboxes()[0][..], "Parsed S1 differs from the engine's");
+        assert_eq!(parse_sboxes("1 2 3"), Err(ParseSBoxError::BadLength(3)));
+        assert_eq!(parse_sboxes("16"), Err(ParseSBoxError::BadOutput(16)));
+        assert_eq!(parse_sboxes("x"), Err(ParseSBoxError::NotANumber("x".to_string())));
+    }
+}
@@ -1,16 +1,81 @@
 use std::io::{Read, Write, BufRead, BufReader, BufWriter};
 use std::io;
+use std::sync::mpsc;
 use std::convert::AsMut;
 use crate::des::details;
-use crate::reinterpret_bytes;
+use crate::des::sdes;
 use crate::reinterpret_bytes::Endianess;
 use details::key_scheduling::*;
+pub use details::{DesXKey, CipherDefinition};
 
 pub const BITS_IN_BLOCK: usize = 64;
 pub const BYTES_IN_BLOCK: usize =
-    (BITS_IN_BLOCK / 8) + (BITS_IN_BLOCK % 8 == 0) as usize;
+    (BITS_IN_BLOCK / 8) + !BITS_IN_BLOCK.is_multiple_of(8) as usize;
 pub const IO_BUF_SIZE: usize = BYTES_IN_BLOCK * 1024 * 4;
 
+/// Implementation of DES, which is used to transform the blocks
+/// Reference - straightforward bit by bit permutations and granulations
+/// Table     - precomputed SP tables (the fastest one)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum Engine {
+    Reference,
+    #[default]
+    Table,
+}
+
+impl Engine {
+    /// Name of the engine, as it’s reported to a user
+    pub fn name(&self) -> &'static str {
+        match self {
+            Engine::Reference => "reference",
+            Engine::Table => "table",
+        }
+    }
+}
+
+/// Mode of operation, which chains the blocks of a stream
+/// ECB - every block is transformed on its own
+/// CBC - a block is XORed with the previous ciphertext block (the
+///       ‘iv’ at first), then encrypted
+/// CFB - a block is XORed with the encryption of the previous
+///       ciphertext block (the ‘iv’ at first)
+/// CTR - a block is XORed with the encryption of the ‘counter’, which
+///       is incremented after every block
+/// Initialization vectors and counters are blocks, as they’re loaded
+/// from the data (in the chosen byte order). CTR, decryption of CBC
+/// and decryption of CFB run on the threads, encryption of CBC and CFB
+/// is sequential
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum Mode {
+    #[default]
+    Ecb,
+    Cbc { iv: u64 },
+    Cfb { iv: u64 },
+    Ctr { counter: u64 },
+}
+
+impl Mode {
+    /// Name of the mode, as it’s reported to a user
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Ecb => "ECB",
+            Mode::Cbc { .. } => "CBC",
+            Mode::Cfb { .. } => "CFB",
+            Mode::Ctr { .. } => "CTR",
+        }
+    }
+
+    /// @returns whether blocks of the mode may run on many threads in
+    /// the direction (‘encrypting’ or not), i.e. the feed of every
+    /// block is known beforehand
+    pub fn is_parallel(&self, encrypting: bool) -> bool {
+        match self {
+            Mode::Ecb | Mode::Ctr { .. } => true,
+            Mode::Cbc { .. } | Mode::Cfb { .. } => !encrypting,
+        }
+    }
+}
+
 /// Main and easy to use function for standard DES encryption. Takes
 /// data from the Read object (buffered), encrypts it and puts it
 /// buffer-wise in the Write object
@@ -18,7 +83,7 @@ pub const IO_BUF_SIZE: usize = BYTES_IN_BLOCK * 1024 * 4;
 pub fn encrypt<R, W>
     (src: R, dst: W, key: u64, endianess: Endianess) -> io::Result<()>
 where R: Read, W: Write {
-    transform_data(src, dst, &details::encrypt_block, KeyScheduler::new_encrypting(key), endianess)
+    encrypt_parallel(src, dst, key, endianess, 1)
 }
 
 /// Main and easy to use function for standard DES decrytion. Takes
@@ -28,91 +93,621 @@ where R: Read, W: Write {
 pub fn decrypt<R, W>
     (src: R, dst: W, key: u64, endianess: Endianess) -> io::Result<()>
 where R: Read, W: Write {
-    transform_data(src, dst, &details::decrypt_block, KeyScheduler::new_decrypting(key),  endianess)
+    decrypt_parallel(src, dst, key, endianess, 1)
+}
+
+/// The same as ‘encrypt’, but blocks are encrypted on ‘threads’
+/// threads at once. Output is the same as of a single threaded run
+/// @returns I/O Error if one occured
+pub fn encrypt_parallel<R, W>
+    (src: R, dst: W, key: u64, endianess: Endianess, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    encrypt_with_engine(src, dst, key, endianess, Engine::Table, threads)
+}
+
+/// The same as ‘decrypt’, but blocks are decrypted on ‘threads’
+/// threads at once. Output is the same as of a single threaded run
+/// @returns I/O Error if one occured
+pub fn decrypt_parallel<R, W>
+    (src: R, dst: W, key: u64, endianess: Endianess, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    decrypt_with_engine(src, dst, key, endianess, Engine::Table, threads)
+}
+
+/// The same as ‘encrypt_parallel’, but the blocks are encrypted with
+/// the given DES ‘engine’
+/// @returns I/O Error if one occured
+pub fn encrypt_with_engine<R, W>
+    (src: R, dst: W, key: u64, endianess: Endianess, engine: Engine, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    encrypt_in_mode(src, dst, key, Mode::Ecb, endianess, engine, threads)
+}
+
+/// The same as ‘decrypt_parallel’, but the blocks are decrypted with
+/// the given DES ‘engine’
+/// @returns I/O Error if one occured
+pub fn decrypt_with_engine<R, W>
+    (src: R, dst: W, key: u64, endianess: Endianess, engine: Engine, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    decrypt_in_mode(src, dst, key, Mode::Ecb, endianess, engine, threads)
 }
 
-/// Performs 3DES encryption algorithm (i.e. encrypt the same data thrice) with
-/// all keys different. Takes data from the Read object (buffered),
-/// encrypts it and puts it buffer-wise in the Write object
+/// The same as ‘encrypt_with_engine’, but the blocks are chained in
+/// the given ‘mode’
+/// @returns I/O Error if one occured
+pub fn encrypt_in_mode<R, W>
+    (src: R, dst: W, key: u64, mode: Mode, endianess: Endianess, engine: Engine, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    transform_chained(src, dst, &engine_block_affector(engine), KeyScheduler::new_encrypting(key),
+                      endianess, threads, Chaining::new(mode, true))
+}
+
+/// The same as ‘decrypt_with_engine’, but the blocks are chained in
+/// the given ‘mode’
+/// @returns I/O Error if one occured
+pub fn decrypt_in_mode<R, W>
+    (src: R, dst: W, key: u64, mode: Mode, endianess: Endianess, engine: Engine, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let chaining = Chaining::new(mode, false);
+    if chaining.encrypts_blocks() {
+        transform_chained(src, dst, &engine_block_affector(engine), KeyScheduler::new_encrypting(key),
+                          endianess, threads, chaining)
+    } else {
+        transform_chained(src, dst, &engine_block_affector(engine), KeyScheduler::new_decrypting(key),
+                          endianess, threads, chaining)
+    }
+}
+
+/// Block function of the DES ‘engine’ (the direction is given by the
+/// round keys)
+/// @returns function, which transforms a block with 16 round keys
+fn engine_block_affector<I>(engine: Engine) -> fn(u64, &mut I) -> u64
+where I: Iterator<Item=Key> {
+    match engine {
+        Engine::Reference => details::encrypt_block,
+        Engine::Table => details::encrypt_block_table,
+    }
+}
+
+/// The same as ‘encrypt_parallel’, but DES is reduced or extended to
+/// a given number of ‘rounds’ (standard DES has 16)
+/// @returns I/O Error if one occured
+pub fn encrypt_with_rounds<R, W>
+    (src: R, dst: W, key: u64, endianess: Endianess, rounds: usize, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let network = details::des_network(rounds);
+    let round_keys = network.encrypting_keys(key).into_iter().cycle();
+    transform_data(src, dst, &move |data, keys: &mut _| network.act_on_block(data, keys), round_keys, endianess, threads)
+}
+
+/// The same as ‘decrypt_parallel’, but DES is reduced or extended to
+/// a given number of ‘rounds’ (standard DES has 16)
+/// @returns I/O Error if one occured
+pub fn decrypt_with_rounds<R, W>
+    (src: R, dst: W, key: u64, endianess: Endianess, rounds: usize, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let network = details::des_network(rounds);
+    let round_keys = network.decrypting_keys(key).into_iter().cycle();
+    transform_data(src, dst, &move |data, keys: &mut _| network.act_on_block(data, keys), round_keys, endianess, threads)
+}
+
+/// The same as ‘encrypt_with_rounds’, but the tables of DES are
+/// taken from the ‘definition’
+/// @returns I/O Error if one occured
+pub fn encrypt_with_definition<R, W>
+    (src: R, dst: W, key: u64, definition: &CipherDefinition, endianess: Endianess, rounds: usize, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let network = definition.network(rounds);
+    let round_keys = network.encrypting_keys(key).into_iter().cycle();
+    transform_data(src, dst, &move |data, keys: &mut _| network.act_on_block(data, keys), round_keys, endianess, threads)
+}
+
+/// The same as ‘decrypt_with_rounds’, but the tables of DES are
+/// taken from the ‘definition’
+/// @returns I/O Error if one occured
+pub fn decrypt_with_definition<R, W>
+    (src: R, dst: W, key: u64, definition: &CipherDefinition, endianess: Endianess, rounds: usize, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let network = definition.network(rounds);
+    let round_keys = network.decrypting_keys(key).into_iter().cycle();
+    transform_data(src, dst, &move |data, keys: &mut _| network.act_on_block(data, keys), round_keys, endianess, threads)
+}
+
+/// The same as ‘encrypt_with_rounds’, but with DES-X: every block is
+/// whitened by XOR with the parts of the ‘key’ before and after DES
+/// @returns I/O Error if one occured
+pub fn desx_encrypt<R, W>
+    (src: R, dst: W, key: DesXKey, endianess: Endianess, rounds: usize, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let network = details::des_network(rounds);
+    let round_keys = network.encrypting_keys(key.key).into_iter().cycle();
+    transform_data(src, dst, &move |data, keys: &mut _| {
+        network.act_on_block(data ^ key.pre_whitening, keys) ^ key.post_whitening
+    }, round_keys, endianess, threads)
+}
+
+/// The same as ‘decrypt_with_rounds’, but with DES-X
+/// @returns I/O Error if one occured
+pub fn desx_decrypt<R, W>
+    (src: R, dst: W, key: DesXKey, endianess: Endianess, rounds: usize, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let network = details::des_network(rounds);
+    let round_keys = network.decrypting_keys(key.key).into_iter().cycle();
+    transform_data(src, dst, &move |data, keys: &mut _| {
+        network.act_on_block(data ^ key.post_whitening, keys) ^ key.pre_whitening
+    }, round_keys, endianess, threads)
+}
+
+/// Encrypts the data with Simplified DES: every byte is a block, so
+/// no padding is needed. Codebook of the 10 bit ‘key’ is built once
+/// @returns I/O Error if one occured
+pub fn sdes_encrypt<R, W>(src: R, dst: W, key: u16) -> io::Result<()>
+where R: Read, W: Write {
+    transform_bytes(src, dst, &sdes::codebook(key, false))
+}
+
+/// Decrypts the data with Simplified DES
+/// @returns I/O Error if one occured
+pub fn sdes_decrypt<R, W>(src: R, dst: W, key: u16) -> io::Result<()>
+where R: Read, W: Write {
+    transform_bytes(src, dst, &sdes::codebook(key, true))
+}
+
+/// Replaces every byte of the data by its entry in the ‘codebook’
+/// @returns I/O Error if one occured
+fn transform_bytes<R, W>(mut src: R, dst: W, codebook: &[u8; 256]) -> io::Result<()>
+where R: Read, W: Write {
+    let mut buf = vec![0u8; IO_BUF_SIZE];
+    let mut write = BufWriter::with_capacity(IO_BUF_SIZE, dst);
+    loop {
+        let len = match src.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for byte in buf[..len].iter_mut() {
+            *byte = codebook[*byte as usize];
+        }
+        write.write_all(&buf[..len])?;
+    }
+    write.flush()
+}
+
+/// Endless round keys of a prepared cipher
+type RoundKeys = std::iter::Cycle<std::vec::IntoIter<Key>>;
+
+/// Cipher with a key, prepared to transform many streams (e.g. a batch
+/// of files): the network and the round keys (or the codebook of
+/// S-DES) are computed once, not per stream
+pub struct PreparedCipher<'a> {
+    kind: PreparedKind<'a>,
+    endianess: Endianess,
+    threads: usize,
+}
+
+enum PreparedKind<'a> {
+    Blocks { block_affector: Box<BlockAffector<'a, RoundKeys>>, round_keys: Vec<Key> },
+    Bytes { codebook: Box<[u8; 1 << sdes::BLOCK_BITS]> },
+}
+
+impl<'a> PreparedCipher<'a> {
+    /// DES of a given number of ‘rounds’, as in ‘encrypt_with_rounds’
+    /// (or ‘decrypt_with_rounds’, if not ‘encrypting’)
+    /// @returns cipher, ready to transform streams
+    pub fn des(key: u64, encrypting: bool, endianess: Endianess, rounds: usize, threads: usize) -> Self {
+        let network = details::des_network(rounds);
+        let round_keys = if encrypting { network.encrypting_keys(key) }
+                         else { network.decrypting_keys(key) };
+        Self::blocks(Box::new(move |data, keys: &mut _| network.act_on_block(data, keys)),
+                     round_keys, endianess, threads)
+    }
+
+    /// DES with the tables of the ‘definition’, as in
+    /// ‘encrypt_with_definition’ / ‘decrypt_with_definition’
+    /// @returns cipher, ready to transform streams
+    pub fn with_definition(key: u64, definition: &'a CipherDefinition, encrypting: bool,
+                           endianess: Endianess, rounds: usize, threads: usize) -> Self {
+        let network = definition.network(rounds);
+        let round_keys = if encrypting { network.encrypting_keys(key) }
+                         else { network.decrypting_keys(key) };
+        Self::blocks(Box::new(move |data, keys: &mut _| network.act_on_block(data, keys)),
+                     round_keys, endianess, threads)
+    }
+
+    /// DES-X, as in ‘desx_encrypt’ / ‘desx_decrypt’
+    /// @returns cipher, ready to transform streams
+    pub fn desx(key: DesXKey, encrypting: bool, endianess: Endianess, rounds: usize, threads: usize) -> Self {
+        let network = details::des_network(rounds);
+        if encrypting {
+            let round_keys = network.encrypting_keys(key.key);
+            Self::blocks(Box::new(move |data, keys: &mut _| {
+                network.act_on_block(data ^ key.pre_whitening, keys) ^ key.post_whitening
+            }), round_keys, endianess, threads)
+        } else {
+            let round_keys = network.decrypting_keys(key.key);
+            Self::blocks(Box::new(move |data, keys: &mut _| {
+                network.act_on_block(data ^ key.post_whitening, keys) ^ key.pre_whitening
+            }), round_keys, endianess, threads)
+        }
+    }
+
+    /// Simplified DES, as in ‘sdes_encrypt’ / ‘sdes_decrypt’
+    /// @returns cipher, ready to transform streams
+    pub fn sdes(key: u16, encrypting: bool) -> Self {
+        Self {
+            kind: PreparedKind::Bytes { codebook: Box::new(sdes::codebook(key, !encrypting)) },
+            endianess: Endianess::Big,
+            threads: 1,
+        }
+    }
+
+    fn blocks(block_affector: Box<BlockAffector<'a, RoundKeys>>, round_keys: Vec<Key>,
+              endianess: Endianess, threads: usize) -> Self {
+        Self { kind: PreparedKind::Blocks { block_affector, round_keys }, endianess, threads }
+    }
+
+    /// Transforms the data from ‘src’ into ‘dst’, the same way as the
+    /// functions above do
+    /// @returns I/O Error if one occured
+    pub fn transform<R, W>(&self, src: R, dst: W) -> io::Result<()>
+    where R: Read, W: Write {
+        match &self.kind {
+            PreparedKind::Blocks { block_affector, round_keys } => transform_data(
+                src, dst, block_affector.as_ref(), round_keys.clone().into_iter().cycle(),
+                self.endianess, self.threads),
+            PreparedKind::Bytes { codebook } => transform_bytes(src, dst, codebook),
+        }
+    }
+}
+
+/// Performs 2DES encryption (i.e. encrypts the data with ‘key1’, then
+/// the result with ‘key2’). It’s no stronger than DES against the
+/// meet-in-the-middle attack, that’s why 3DES exists
+/// @returns I/O Error if one occured
+pub fn double_encrypt<R, W>
+    (src: R, dst: W, (key1, key2): (u64, u64), endianess: Endianess, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let network = details::des_network(details::DES_ROUNDS_NUMBER);
+    let round_keys = network.encrypting_keys(key1).into_iter()
+        .chain(network.encrypting_keys(key2)).collect::<Vec<_>>().into_iter().cycle();
+    transform_data(src, dst, &move |data, keys: &mut _| {
+        network.act_on_block(network.act_on_block(data, keys), keys)
+    }, round_keys, endianess, threads)
+}
+
+/// Performs 2DES decryption (i.e. decrypts the data with ‘key2’, then
+/// the result with ‘key1’)
+/// @returns I/O Error if one occured
+pub fn double_decrypt<R, W>
+    (src: R, dst: W, (key1, key2): (u64, u64), endianess: Endianess, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let network = details::des_network(details::DES_ROUNDS_NUMBER);
+    let round_keys = network.decrypting_keys(key2).into_iter()
+        .chain(network.decrypting_keys(key1)).collect::<Vec<_>>().into_iter().cycle();
+    transform_data(src, dst, &move |data, keys: &mut _| {
+        network.act_on_block(network.act_on_block(data, keys), keys)
+    }, round_keys, endianess, threads)
+}
+
+/// Performs 3DES encryption (EDE: encrypts the data with ‘key1’,
+/// decrypts the result with ‘key2’ and encrypts it with ‘key3’). With
+/// all keys equal, it’s DES. Takes data from the Read object
+/// (buffered), encrypts it and puts it buffer-wise in the Write object
 /// @returns I/O Error if one occured
 pub fn triple_encrypt<R, W>
-    (src: R, dst: W, (key1, key2, key3): (u64, u64, u64), endianess: Endianess) -> io::Result<()>
+    (src: R, dst: W, keys: (u64, u64, u64), endianess: Endianess) -> io::Result<()>
 where R: Read, W: Write {
-    Ok( () )
+    triple_encrypt_in_mode(src, dst, keys, Mode::Ecb, endianess, Engine::Table, 1)
 }
 
-/// Performs 3DES decryption algorithm (i.e. encrypt the same data thrice) with
-/// all keys different. Takes data from the Read object (buffered),
-/// decrypts it and puts it buffer-wise in the Write object
+/// Performs 3DES decryption (i.e. decrypts the data with ‘key3’,
+/// encrypts the result with ‘key2’ and decrypts it with ‘key1’). Takes
+/// data from the Read object (buffered), decrypts it and puts it
+/// buffer-wise in the Write object
 /// @returns I/O Error if one occured
 pub fn triple_decrypt<R, W>
-    (src: R, dst: W, (key1, key2, key3): (u64, u64, u64), endianess: Endianess) -> io::Result<()>
+    (src: R, dst: W, keys: (u64, u64, u64), endianess: Endianess) -> io::Result<()>
+where R: Read, W: Write {
+    triple_decrypt_in_mode(src, dst, keys, Mode::Ecb, endianess, Engine::Table, 1)
+}
+
+/// The same as ‘triple_encrypt’, but the blocks are chained in the
+/// given ‘mode’ and transformed with the DES ‘engine’ on ‘threads’
+/// threads
+/// @returns I/O Error if one occured
+pub fn triple_encrypt_in_mode<R, W>
+    (src: R, dst: W, keys: (u64, u64, u64), mode: Mode, endianess: Endianess, engine: Engine, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    triple_in_mode(src, dst, keys, Chaining::new(mode, true), endianess, engine, threads)
+}
+
+/// The same as ‘triple_decrypt’, but the blocks are chained in the
+/// given ‘mode’ and transformed with the DES ‘engine’ on ‘threads’
+/// threads
+/// @returns I/O Error if one occured
+pub fn triple_decrypt_in_mode<R, W>
+    (src: R, dst: W, keys: (u64, u64, u64), mode: Mode, endianess: Endianess, engine: Engine, threads: usize) -> io::Result<()>
 where R: Read, W: Write {
-    Ok( () )
+    triple_in_mode(src, dst, keys, Chaining::new(mode, false), endianess, engine, threads)
+}
+
+/// 3DES, which blocks are transformed in the direction, the
+/// ‘chaining’ needs
+/// @returns I/O Error if one occured
+fn triple_in_mode<R, W>
+    (src: R, dst: W, (key1, key2, key3): (u64, u64, u64), chaining: Chaining, endianess: Endianess,
+     engine: Engine, threads: usize) -> io::Result<()>
+where R: Read, W: Write {
+    let rounds = details::DES_ROUNDS_NUMBER;
+    let round_keys: Vec<Key> = if chaining.encrypts_blocks() {
+        KeyScheduler::new_encrypting(key1).take(rounds)
+            .chain(KeyScheduler::new_decrypting(key2).take(rounds))
+            .chain(KeyScheduler::new_encrypting(key3).take(rounds))
+            .collect()
+    } else {
+        KeyScheduler::new_decrypting(key3).take(rounds)
+            .chain(KeyScheduler::new_encrypting(key2).take(rounds))
+            .chain(KeyScheduler::new_decrypting(key1).take(rounds))
+            .collect()
+    };
+    let block = engine_block_affector::<RoundKeys>(engine);
+    transform_chained(src, dst, &move |data, keys: &mut _| block(block(block(data, keys), keys), keys),
+                      round_keys.into_iter().cycle(), endianess, threads, chaining)
 }
 
 /// Supportive function to DES, since both encryption and decryption
 /// goes essentially the same way (but with a different keys generation),
 /// this function just applies the same steps on Read and Write
-/// objects and encrypts/decrypts them.
+/// objects and encrypts/decrypts them (every block on its own)
 /// @returns I/O Error if one occured
 fn transform_data<R, W, I>
-    (mut src: R, dst: W,  block_affector: &Fn(u64, &mut I) -> u64, mut key_iterator: I, endianess: Endianess) -> io::Result<()>
-where R: Read, W: Write, I: Iterator<Item=Key> {
-    let mut read_buf  = [0u8; IO_BUF_SIZE];
-    let mut write = BufWriter::with_capacity(IO_BUF_SIZE, dst);
-    // TODO: concurrency
-    // FIXME: somehow mutate inside of BufReader
-    // TODO: endianess
-    loop {
-        let slice_to_write;
-        let processed_size = src.read(&mut read_buf)?;
-        match processed_size {
-            0 => break,
-            len if len < IO_BUF_SIZE => {
-                const TYPE_SIZE: usize = std::mem::size_of::<u64>();
-                let tail_size = len % TYPE_SIZE;
-                let padding = if tail_size == 0 { 0 } else { TYPE_SIZE - tail_size };
-                if tail_size > 0 {
-                    //read_buf[padding..len+padding].copy_from_slice(&read_buf[..len]);
-                    // unsafe{ std::ptr::copy(
-                    //     read_buf[len - tail_size..len].as_ptr(),
-                    //     read_buf[len - tail_size + padding..len+padding].as_mut_ptr(),
-                    //     len
-                    // );}
-                }
-                for padded_mem in read_buf[len..len + padding].iter_mut() {
-                    *padded_mem = 0;
+    (src: R, dst: W, block_affector: &BlockAffector<'_, I>, key_iterator: I, endianess: Endianess, threads: usize) -> io::Result<()>
+where R: Read, W: Write, I: Iterator<Item=Key> + Clone + Send {
+    transform_chained(src, dst, block_affector, key_iterator, endianess, threads,
+                      Chaining::new(Mode::Ecb, true))
+}
+
+/// The same as ‘transform_data’, but the blocks are chained as the
+/// ‘chaining’ tells. With ‘threads’ > 1 and a mode, which can run in
+/// parallel, workers are spawned once for the whole stream, and whole
+/// blocks of every long read are split between them (each piece starts
+/// with its own chaining state), then written in the original order.
+/// Short reads are transformed on the calling thread. A partial block
+/// is carried to the next read, since pipes and sockets may return
+/// less than asked in the middle of the data, and only the last block
+/// is padded with zeros (when the source is over)
+/// @returns I/O Error if one occured
+fn transform_chained<R, W, I>
+    (mut src: R, dst: W, block_affector: &BlockAffector<'_, I>, mut key_iterator: I, endianess: Endianess,
+     threads: usize, mut chaining: Chaining) -> io::Result<()>
+where R: Read, W: Write, I: Iterator<Item=Key> + Clone + Send {
+    let threads = if chaining.is_parallel() { threads.max(1) } else { 1 };
+    let buf_size = IO_BUF_SIZE * threads;
+    // dispatching to workers doesn’t pay off for a few blocks
+    let parallel_min_size = buf_size / 2;
+    let mut read_buf = vec![0u8; buf_size];
+    let mut write = BufWriter::with_capacity(buf_size, dst);
+    std::thread::scope(|scope| {
+        let mut workers = if threads > 1 {
+            Some(Workers::spawn(scope, threads, block_affector, &key_iterator, endianess))
+        } else {
+            None
+        };
+        let mut transform = |blocks: &mut [u8], key_iterator: &mut I, chaining: &mut Chaining| {
+            match workers.as_mut() {
+                Some(workers) if blocks.len() >= parallel_min_size => workers.transform(blocks, chaining),
+                _ => transform_padded_slice(blocks, block_affector, key_iterator, endianess, chaining),
+            }
+        };
+        // bytes at the beginning of the buffer, which don’t make a whole
+        // block yet
+        let mut carried = 0;
+        loop {
+            let len = match src.read(&mut read_buf[carried..]) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let filled = carried + len;
+            let whole_blocks = filled - filled % BYTES_IN_BLOCK;
+            transform(&mut read_buf[..whole_blocks], &mut key_iterator, &mut chaining);
+            write.write_all(&read_buf[..whole_blocks])?;
+            read_buf.copy_within(whole_blocks..filled, 0);
+            carried = filled - whole_blocks;
+        }
+        if carried > 0 {
+            for padded_mem in read_buf[carried..BYTES_IN_BLOCK].iter_mut() {
+                *padded_mem = 0;
+            }
+            transform(&mut read_buf[..BYTES_IN_BLOCK], &mut key_iterator, &mut chaining);
+            write.write_all(&read_buf[..BYTES_IN_BLOCK])?;
+        }
+        write.flush()
+    })
+}
+
+/// Function, that transforms a single block with the keys from the
+/// key iterator, and can be shared between threads (it may borrow
+/// tables for lifetime ‘'a’)
+type BlockAffector<'a, I> = dyn Fn(u64, &mut I) -> u64 + Sync + 'a;
+
+/// Mode of a stream in one direction, with the block, which carries
+/// from one block to the next one
+#[derive(Copy, Clone, Debug)]
+struct Chaining {
+    mode: Mode,
+    encrypting: bool,
+    /// the previous ciphertext block for CBC and CFB, the counter of
+    /// the next block for CTR
+    feed: u64,
+}
+
+impl Chaining {
+    /// Chaining at the beginning of a stream
+    fn new(mode: Mode, encrypting: bool) -> Self {
+        let feed = match mode {
+            Mode::Ecb => 0,
+            Mode::Cbc { iv } | Mode::Cfb { iv } => iv,
+            Mode::Ctr { counter } => counter,
+        };
+        Self { mode, encrypting, feed }
+    }
+
+    /// @returns whether the block function is the encrypting one (CFB
+    /// and CTR decrypt with the encryption of the feed)
+    fn encrypts_blocks(&self) -> bool {
+        match self.mode {
+            Mode::Cfb { .. } | Mode::Ctr { .. } => true,
+            Mode::Ecb | Mode::Cbc { .. } => self.encrypting,
+        }
+    }
+
+    /// @returns whether a block may be transformed before the previous
+    /// ones
+    fn is_parallel(&self) -> bool {
+        self.mode.is_parallel(self.encrypting)
+    }
+
+    /// Chaining after the whole blocks of ‘bytes’, which aren’t
+    /// transformed yet (only for the modes, which are parallel)
+    /// @returns chaining of the block after ‘bytes’
+    fn skipped(&self, bytes: &[u8], endianess: Endianess) -> Self {
+        let blocks = bytes.len() / BYTES_IN_BLOCK;
+        let feed = match self.mode {
+            Mode::Ecb => self.feed,
+            Mode::Ctr { .. } => self.feed.wrapping_add(blocks as u64),
+            Mode::Cbc { .. } | Mode::Cfb { .. } if blocks == 0 => self.feed,
+            // the last ciphertext block
+            Mode::Cbc { .. } | Mode::Cfb { .. } =>
+                load_block(&bytes[(blocks - 1) * BYTES_IN_BLOCK..blocks * BYTES_IN_BLOCK], endianess),
+        };
+        Self { feed, ..*self }
+    }
+}
+
+type WorkerChannels = (mpsc::Sender<(Vec<u8>, Chaining)>, mpsc::Receiver<Vec<u8>>);
+
+/// Persistent worker threads of a stream. Each worker has its own copy
+/// of the key iterator (taken at the beginning of a block, so it stays
+/// there after whole blocks), receives contiguous pieces of whole
+/// blocks with their chaining through its channel and sends them back
+/// transformed. Workers stop, when the pool is dropped
+struct Workers {
+    /// sender of pieces and receiver of results of each worker
+    channels: Vec<WorkerChannels>,
+    /// buffers, returned by workers, reused for the next pieces
+    spare_buffers: Vec<Vec<u8>>,
+    endianess: Endianess,
+}
+
+impl Workers {
+    /// Spawns ‘threads’ workers in the ‘scope’
+    /// @returns pool of the spawned workers
+    fn spawn<'scope, 'env, I>(scope: &'scope std::thread::Scope<'scope, 'env>, threads: usize,
+                              block_affector: &'env BlockAffector<'_, I>, key_iterator: &I,
+                              endianess: Endianess) -> Self
+    where I: Iterator<Item=Key> + Clone + Send + 'scope {
+        let channels = (0..threads).map(|_| {
+            let (job_sender, job_receiver) = mpsc::channel::<(Vec<u8>, Chaining)>();
+            let (result_sender, result_receiver) = mpsc::channel();
+            let mut keys = key_iterator.clone();
+            scope.spawn(move || {
+                for (mut piece, mut chaining) in job_receiver {
+                    transform_padded_slice(&mut piece, block_affector, &mut keys, endianess, &mut chaining);
+                    if result_sender.send(piece).is_err() { break; }
                 }
-                slice_to_write = &mut read_buf[..len + padding];
-            },
-            _ => slice_to_write = &mut read_buf,
+            });
+            (job_sender, result_receiver)
+        }).collect();
+        Self { channels, spare_buffers: vec![], endianess }
+    }
+
+    /// Splits whole blocks of the slice into contiguous pieces, one per
+    /// worker, and transforms them inplace. The ‘chaining’ (of a mode,
+    /// which is parallel) is moved past the slice
+    fn transform(&mut self, bytes: &mut [u8], chaining: &mut Chaining) {
+        let blocks_total = bytes.len() / BYTES_IN_BLOCK;
+        let piece_size = blocks_total.div_ceil(self.channels.len()).max(1) * BYTES_IN_BLOCK;
+        for (piece, (job_sender, _)) in bytes.chunks(piece_size).zip(self.channels.iter()) {
+            let mut buffer = self.spare_buffers.pop().unwrap_or_default();
+            buffer.clear();
+            buffer.extend_from_slice(piece);
+            job_sender.send((buffer, *chaining)).expect("Worker thread has stopped");
+            *chaining = chaining.skipped(piece, self.endianess);
+        }
+        // results are taken from the same workers in the same order
+        for (piece, (_, result_receiver)) in bytes.chunks_mut(piece_size).zip(self.channels.iter()) {
+            let buffer = result_receiver.recv().expect("Worker thread has stopped");
+            piece.copy_from_slice(&buffer);
+            self.spare_buffers.push(buffer);
         }
-        transform_padded_slice(slice_to_write, block_affector, &mut key_iterator);
-        write.write_all(slice_to_write)?;
     }
-    Ok( () )
-} 
+}
 
-/// Encrypts / decrypts (considering function pointer action) a 64 bit
-/// block of data inplace. Respects target machine’s endianess
-// TODO: asmut
-fn transform_padded_slice<I>(bytes: &mut [u8], block_affector: &Fn(u64, &mut I) -> u64, key_iterator: &mut I)
+/// Encrypts / decrypts (considering function pointer action) every 64
+/// bit block of data inplace, chained as the ‘chaining’ tells (it’s
+/// moved past the data). Each 8 bytes are loaded into a block in the
+/// given byte order, and the result is stored in the same order:
+/// Big    - the first byte is the highest (as DES standard reads data)
+/// Little - the first byte is the lowest (as little-endian machines
+///          load a u64 from memory)
+/// Output doesn’t depend on the target machine’s endianess
+fn transform_padded_slice<I>(bytes: &mut [u8], block_affector: &BlockAffector<'_, I>, key_iterator: &mut I,
+                             endianess: Endianess, chaining: &mut Chaining)
 where I: Iterator<Item=Key>{
-    let blocks = reinterpret_bytes::as_slice_of::<u64>(bytes);
-    for block in blocks.iter_mut() {
-        if cfg!(target_endian = "little") {
-            *block = block_affector(block.to_be(), key_iterator).to_be();
-        } else {
-            *block = block_affector(*block, key_iterator);
-        }
+    for chunk in bytes.chunks_exact_mut(BYTES_IN_BLOCK) {
+        let block = load_block(chunk, endianess);
+        let mut cipher = |data| block_affector(data, key_iterator);
+        let result = match (chaining.mode, chaining.encrypting) {
+            (Mode::Ecb, _) => cipher(block),
+            (Mode::Cbc { .. }, true) => {
+                chaining.feed = cipher(block ^ chaining.feed);
+                chaining.feed
+            },
+            (Mode::Cbc { .. }, false) => {
+                let plain = cipher(block) ^ chaining.feed;
+                chaining.feed = block;
+                plain
+            },
+            (Mode::Cfb { .. }, true) => {
+                chaining.feed = block ^ cipher(chaining.feed);
+                chaining.feed
+            },
+            (Mode::Cfb { .. }, false) => {
+                let plain = block ^ cipher(chaining.feed);
+                chaining.feed = block;
+                plain
+            },
+            (Mode::Ctr { .. }, _) => {
+                let result = block ^ cipher(chaining.feed);
+                chaining.feed = chaining.feed.wrapping_add(1);
+                result
+            },
+        };
+        let result = match endianess {
+            Endianess::Big => result.to_be_bytes(),
+            Endianess::Little => result.to_le_bytes(),
+        };
+        chunk.copy_from_slice(&result);
     }
 }
 
+/// @returns block of the 8 bytes of ‘chunk’ in the given byte order
+fn load_block(chunk: &[u8], endianess: Endianess) -> u64 {
+    let mut block = [0; BYTES_IN_BLOCK];
+    block.copy_from_slice(chunk);
+    match endianess {
+        Endianess::Big => u64::from_be_bytes(block),
+        Endianess::Little => u64::from_le_bytes(block),
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::des::analysis::Random;
 
     #[test]
     fn test_encrypt(){
@@ -165,5 +760,393 @@ mod tests {
                 .zip(output_test.iter())
                 .all(|(&a, &b)| a == b), "Basic DES decryption of a stream is wrong");
     }
+
+    #[test]
+    fn test_little_endian() {
+        let data: Vec<u8> = (0..64).map(|i| (i * 37 % 251) as u8).collect();
+        let key = 0x0E329232EA6D0D73;
+        // a tool, which loads every 8 bytes as a native little-endian u64
+        let expected: Vec<u8> = data.chunks(BYTES_IN_BLOCK).flat_map(|chunk| {
+            let mut block = [0; BYTES_IN_BLOCK];
+            block.copy_from_slice(chunk);
+            details::encrypt_block_separately(u64::from_le_bytes(block), key).to_le_bytes()
+        }).collect();
+        for &threads in [1, 3].iter() {
+            let mut encrypted = vec![];
+            encrypt_parallel(&data[..], &mut encrypted, key, Endianess::Little, threads)
+                .expect("Encryption internal error");
+            assert!(encrypted == expected,
+                    "Little-endian blocks should be loaded and stored as little-endian u64");
+            let mut decrypted = vec![];
+            decrypt_parallel(&encrypted[..], &mut decrypted, key, Endianess::Little, threads)
+                .expect("Decryption internal error");
+            assert!(decrypted == data, "Little-endian decryption doesn't restore the data");
+        }
+        let mut big = vec![];
+        encrypt(&data[..], &mut big, key, Endianess::Big).expect("Encryption internal error");
+        assert!(big != expected, "Byte order should affect the output");
+    }
+
+    #[test]
+    fn test_byte_orders_mirror_each_other() {
+        // little-endian encryption is big-endian encryption of the
+        // blocks with reversed bytes
+        let data: Vec<u8> = (0..40).collect();
+        let reversed: Vec<u8> = data.chunks(BYTES_IN_BLOCK)
+            .flat_map(|chunk| chunk.iter().rev().cloned().collect::<Vec<_>>()).collect();
+        let key = 0x133457799BBCDFF1;
+        let mut little = vec![];
+        encrypt(&data[..], &mut little, key, Endianess::Little).expect("Encryption internal error");
+        let mut big = vec![];
+        encrypt(&reversed[..], &mut big, key, Endianess::Big).expect("Encryption internal error");
+        let big_reversed: Vec<u8> = big.chunks(BYTES_IN_BLOCK)
+            .flat_map(|chunk| chunk.iter().rev().cloned().collect::<Vec<_>>()).collect();
+        assert!(little == big_reversed, "Byte orders should only reverse bytes of every block");
+    }
+
+    /// Source, which returns random short reads (and is interrupted
+    /// sometimes), as pipes and sockets do
+    struct ShortReads<'a> {
+        data: &'a [u8],
+        random: Random,
+    }
+
+    impl Read for ShortReads<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let dice = self.random.next_u64();
+            if dice.is_multiple_of(7) {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+            }
+            let len = (dice as usize / 7 % 20 + 1).min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_short_reads() {
+        let key = 0x0E329232EA6D0D73;
+        for &size in [0, 5, 8, 1000, IO_BUF_SIZE + 3].iter() {
+            let data: Vec<u8> = (0..size).map(|i| (i * 31 % 251) as u8).collect();
+            let mut expected = vec![];
+            encrypt(&data[..], &mut expected, key, Endianess::Big)
+                .expect("Encryption internal error");
+            assert_eq!(expected.len(), size.div_ceil(BYTES_IN_BLOCK) * BYTES_IN_BLOCK,
+                       "Only the last block should be padded");
+            for (seed, &threads) in [1, 3].iter().enumerate() {
+                let source = ShortReads { data: &data, random: Random::new(seed as u64) };
+                let mut encrypted = vec![];
+                encrypt_parallel(source, &mut encrypted, key, Endianess::Big, threads)
+                    .expect("Encryption internal error");
+                assert!(encrypted == expected,
+                        "Short reads of {} bytes on {} thread(s) changed the output", size, threads);
+                let source = ShortReads { data: &encrypted, random: Random::new(seed as u64 + 7) };
+                let mut decrypted = vec![];
+                decrypt_parallel(source, &mut decrypted, key, Endianess::Big, threads)
+                    .expect("Decryption internal error");
+                assert!(decrypted[..size] == data[..],
+                        "Short reads of {} bytes don't decrypt back", size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_matches_single_threaded() {
+        // a few buffers long (the tail is shorter than a half of the
+        // buffer on 8 threads), with a partial block in the end
+        let data: Vec<u8> = (0..IO_BUF_SIZE * 9 + 13)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect();
+        let key = 0x0E329232EA6D0D73;
+        let mut single = vec![];
+        encrypt(&data[..], &mut single, key, Endianess::Big)
+            .expect("Encryption internal error");
+        for threads in [2, 3, 8].iter().cloned() {
+            let mut parallel = vec![];
+            encrypt_parallel(&data[..], &mut parallel, key, Endianess::Big, threads)
+                .expect("Parallel encryption internal error");
+            assert!(single == parallel,
+                    "Encryption on {} threads differs from single threaded", threads);
+            let mut decrypted = vec![];
+            decrypt_parallel(&parallel[..], &mut decrypted, key, Endianess::Big, threads)
+                .expect("Parallel decryption internal error");
+            assert!(decrypted[..data.len()] == data[..],
+                    "Decryption on {} threads doesn't restore the data", threads);
+        }
+    }
+
+    #[test]
+    fn test_modes() {
+        // FIPS 81, appendices B-D: "Now is the time for all "
+        let key = 0x0123456789ABCDEF;
+        let iv = 0x1234567890ABCDEF;
+        let data = b"Now is the time for all ";
+        let vectors: [(Mode, [u64; 3]); 3] = [
+            (Mode::Ecb, [0x3FA40E8A984D4815, 0x6A271787AB8883F9, 0x893D51EC4B563B53]),
+            (Mode::Cbc { iv }, [0xE5C7CDDE872BF27C, 0x43E934008C389C0F, 0x683788499A7C05F6]),
+            (Mode::Cfb { iv }, [0xF3096249C7F46E51, 0xA69E839B1A92F784, 0x03467133898EA622]),
+        ];
+        for (mode, blocks) in vectors.iter() {
+            let expected: Vec<u8> = blocks.iter().flat_map(|block| block.to_be_bytes()).collect();
+            let mut encrypted = vec![];
+            encrypt_in_mode(&data[..], &mut encrypted, key, *mode, Endianess::Big, Engine::Table, 1)
+                .expect("Encryption internal error");
+            assert!(encrypted == expected, "{} encryption differs from FIPS 81", mode.name());
+            let mut decrypted = vec![];
+            decrypt_in_mode(&encrypted[..], &mut decrypted, key, *mode, Endianess::Big, Engine::Reference, 1)
+                .expect("Decryption internal error");
+            assert!(decrypted == data, "{} decryption doesn't restore the data", mode.name());
+        }
+        // CTR is XOR with the encrypted counters
+        let counter = u64::MAX;
+        let mut encrypted = vec![];
+        encrypt_in_mode(&data[..], &mut encrypted, key, Mode::Ctr { counter }, Endianess::Big, Engine::Table, 1)
+            .expect("Encryption internal error");
+        let expected: Vec<u8> = data.chunks(BYTES_IN_BLOCK).zip(0..).flat_map(|(chunk, i)| {
+            let pad = details::encrypt_block_separately(counter.wrapping_add(i), key).to_be_bytes();
+            chunk.iter().zip(pad.iter()).map(|(a, b)| a ^ b).collect::<Vec<_>>()
+        }).collect();
+        assert!(encrypted == expected, "CTR should XOR the data with the encrypted counters");
+    }
+
+    #[test]
+    fn test_parallel_modes_match_single_threaded() {
+        let data: Vec<u8> = (0..IO_BUF_SIZE * 5 + 13)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect();
+        let key = 0x0E329232EA6D0D73;
+        let modes = [Mode::Ecb, Mode::Cbc { iv: 0x1234567890ABCDEF },
+                     Mode::Cfb { iv: 0xFEDCBA0987654321 }, Mode::Ctr { counter: u64::MAX - 5 }];
+        let configurations = [(2, Endianess::Big), (3, Endianess::Little), (8, Endianess::Big)];
+        for &mode in modes.iter() {
+            for (seed, &(threads, endianess)) in configurations.iter().enumerate() {
+                let mut encrypted = vec![];
+                encrypt_in_mode(&data[..], &mut encrypted, key, mode, endianess, Engine::Table, 1)
+                    .expect("Encryption internal error");
+                let mut decrypted = vec![];
+                decrypt_in_mode(&encrypted[..], &mut decrypted, key, mode, endianess, Engine::Table, 1)
+                    .expect("Decryption internal error");
+                assert!(decrypted[..data.len()] == data[..],
+                        "{} decryption doesn't restore the data", mode.name());
+                let mut parallel = vec![];
+                encrypt_in_mode(&data[..], &mut parallel, key, mode, endianess, Engine::Table, threads)
+                    .expect("Parallel encryption internal error");
+                assert!(parallel == encrypted,
+                        "{} encryption on {} threads differs from single threaded", mode.name(), threads);
+                let mut parallel = vec![];
+                decrypt_in_mode(&encrypted[..], &mut parallel, key, mode, endianess, Engine::Table, threads)
+                    .expect("Parallel decryption internal error");
+                assert!(parallel == decrypted,
+                        "{} decryption on {} threads differs from single threaded", mode.name(), threads);
+            }
+            // the pieces of the workers (a full buffer of 2 threads),
+            // short reads on this thread, which carry a partial block,
+            // and the workers again
+            let mut encrypted = vec![];
+            encrypt_in_mode(&data[..], &mut encrypted, key, mode, Endianess::Big, Engine::Table, 1)
+                .expect("Encryption internal error");
+            let (first, rest) = encrypted.split_at(IO_BUF_SIZE * 2 + 13);
+            let (second, third) = rest.split_at(100);
+            let mut decrypted = vec![];
+            decrypt_in_mode(first.chain(second).chain(third), &mut decrypted, key, mode,
+                            Endianess::Big, Engine::Table, 2)
+                .expect("Parallel decryption internal error");
+            assert!(decrypted[..data.len()] == data[..],
+                    "{} decryption of mixed reads on 2 threads doesn't restore the data", mode.name());
+        }
+    }
+
+    #[test]
+    fn test_double_des() {
+        let data: Vec<u8> = (0..100).collect();
+        let keys = (0x0E329232EA6D0D73, 0x133457799BBCDFF1);
+        let mut once = vec![];
+        encrypt(&data[..], &mut once, keys.0, Endianess::Big)
+            .expect("Encryption internal error");
+        let mut twice = vec![];
+        encrypt(&once[..], &mut twice, keys.1, Endianess::Big)
+            .expect("Encryption internal error");
+        let mut double = vec![];
+        double_encrypt(&data[..], &mut double, keys, Endianess::Big, 2)
+            .expect("Encryption internal error");
+        assert!(double == twice, "2DES should encrypt with the first key, then the second");
+        let mut decrypted = vec![];
+        double_decrypt(&double[..], &mut decrypted, keys, Endianess::Big, 1)
+            .expect("Decryption internal error");
+        assert!(decrypted[..data.len()] == data[..], "2DES decryption doesn't restore the data");
+    }
+
+    #[test]
+    fn test_triple_des() {
+        // SP 800-67, example of TDEA ECB (its typo is in the plaintext)
+        let keys = (0x0123456789ABCDEF, 0x23456789ABCDEF01, 0x456789ABCDEF0123);
+        let data = b"The qufck brown fox jump";
+        let expected: Vec<u8> = [0xA826FD8CE53B855F_u64, 0xCCE21C8112256FE6, 0x68D5C05DD9B6B900]
+            .iter().flat_map(|block| block.to_be_bytes()).collect();
+        let mut encrypted = vec![];
+        triple_encrypt(&data[..], &mut encrypted, keys, Endianess::Big)
+            .expect("Encryption internal error");
+        assert!(encrypted == expected, "3DES encryption differs from SP 800-67");
+        let mut decrypted = vec![];
+        triple_decrypt(&encrypted[..], &mut decrypted, keys, Endianess::Big)
+            .expect("Decryption internal error");
+        assert!(decrypted == data, "3DES decryption doesn't restore the data");
+
+        let key = 0x0E329232EA6D0D73;
+        let data: Vec<u8> = (0..IO_BUF_SIZE * 3 + 5).map(|i| (i * 31 % 251) as u8).collect();
+        for &mode in [Mode::Ecb, Mode::Cbc { iv: 0x1234567890ABCDEF }, Mode::Ctr { counter: 7 }].iter() {
+            let mut des = vec![];
+            encrypt_in_mode(&data[..], &mut des, key, mode, Endianess::Big, Engine::Table, 1)
+                .expect("Encryption internal error");
+            let mut triple = vec![];
+            triple_encrypt_in_mode(&data[..], &mut triple, (key, key, key), mode, Endianess::Big,
+                                   Engine::Reference, 2)
+                .expect("Encryption internal error");
+            assert!(triple == des, "{} 3DES with equal keys should be DES", mode.name());
+            let mut encrypted = vec![];
+            triple_encrypt_in_mode(&data[..], &mut encrypted, keys, mode, Endianess::Big, Engine::Table, 3)
+                .expect("Encryption internal error");
+            let mut decrypted = vec![];
+            triple_decrypt_in_mode(&encrypted[..], &mut decrypted, keys, mode, Endianess::Big, Engine::Table, 3)
+                .expect("Decryption internal error");
+            assert!(decrypted[..data.len()] == data[..],
+                    "{} 3DES decryption on threads doesn't restore the data", mode.name());
+        }
+    }
+
+    #[test]
+    fn test_desx() {
+        let data: Vec<u8> = (0..100).collect();
+        let key = DesXKey::new(0x0123456789ABCDEF, 0xF1E0D3C2B5A49786, 0xFEDCBA9876543210);
+        let mut encrypted = vec![];
+        desx_encrypt(&data[..], &mut encrypted, key, Endianess::Big, 16, 2)
+            .expect("Encryption internal error");
+        let first = u64::from_be_bytes([0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(encrypted[..8], details::desx_encrypt_block(first, key).to_be_bytes(),
+                   "DES-X stream should encrypt blocks as DES-X does");
+        let mut decrypted = vec![];
+        desx_decrypt(&encrypted[..], &mut decrypted, key, Endianess::Big, 16, 1)
+            .expect("Decryption internal error");
+        assert!(decrypted[..data.len()] == data[..], "DES-X decryption doesn't restore the data");
+    }
+
+    #[test]
+    fn test_sdes() {
+        let data: Vec<u8> = (0..=255).collect();
+        let key = 0b10100_00010;
+        let mut encrypted = vec![];
+        sdes_encrypt(&data[..], &mut encrypted, key).expect("Encryption internal error");
+        assert_eq!(encrypted.len(), data.len(), "S-DES doesn't pad the data");
+        assert_eq!(encrypted[0x97], 0x38, "Textbook example inside of a stream");
+        let mut decrypted = vec![];
+        sdes_decrypt(&encrypted[..], &mut decrypted, key).expect("Decryption internal error");
+        assert!(decrypted == data, "S-DES decryption doesn't restore the data");
+    }
+
+    #[test]
+    fn test_definition() {
+        let definition = CipherDefinition::parse(include_str!("../../bin/des.def"))
+            .expect("Valid DES definition");
+        let data: Vec<u8> = (0..100).collect();
+        let key = 0x0E329232EA6D0D73;
+        let mut expected = vec![];
+        encrypt(&data[..], &mut expected, key, Endianess::Big)
+            .expect("Encryption internal error");
+        let mut encrypted = vec![];
+        encrypt_with_definition(&data[..], &mut encrypted, key, &definition, Endianess::Big, 16, 2)
+            .expect("Encryption internal error");
+        assert!(encrypted == expected, "DES definition should encrypt as DES");
+        let mut decrypted = vec![];
+        decrypt_with_definition(&encrypted[..], &mut decrypted, key, &definition, Endianess::Big, 16, 1)
+            .expect("Decryption internal error");
+        assert!(decrypted[..data.len()] == data[..], "DES definition decryption doesn't restore the data");
+    }
+
+    #[test]
+    fn test_prepared_cipher() {
+        let data: Vec<u8> = (0..=255).collect();
+        let key = 0x0E329232EA6D0D73;
+        let desx_key = DesXKey::new(key, 0xF1E0D3C2B5A49786, 0xFEDCBA9876543210);
+        let definition = CipherDefinition::parse(include_str!("../../bin/des.def"))
+            .expect("Valid DES definition");
+        let mut expected = vec![vec![]; 4];
+        encrypt_with_rounds(&data[..], &mut expected[0], key, Endianess::Little, 8, 1)
+            .expect("Encryption internal error");
+        desx_encrypt(&data[..], &mut expected[1], desx_key, Endianess::Big, 16, 1)
+            .expect("Encryption internal error");
+        encrypt_with_definition(&data[..], &mut expected[2], key, &definition, Endianess::Big, 16, 1)
+            .expect("Encryption internal error");
+        sdes_encrypt(&data[..], &mut expected[3], 0b10100_00010)
+            .expect("Encryption internal error");
+        let ciphers = [
+            (PreparedCipher::des(key, true, Endianess::Little, 8, 2),
+             PreparedCipher::des(key, false, Endianess::Little, 8, 1)),
+            (PreparedCipher::desx(desx_key, true, Endianess::Big, 16, 2),
+             PreparedCipher::desx(desx_key, false, Endianess::Big, 16, 1)),
+            (PreparedCipher::with_definition(key, &definition, true, Endianess::Big, 16, 1),
+             PreparedCipher::with_definition(key, &definition, false, Endianess::Big, 16, 2)),
+            (PreparedCipher::sdes(0b10100_00010, true), PreparedCipher::sdes(0b10100_00010, false)),
+        ];
+        for ((encryptor, decryptor), expected) in ciphers.iter().zip(expected.iter()) {
+            // the same prepared cipher is reused for several streams
+            for _ in 0..2 {
+                let mut encrypted = vec![];
+                encryptor.transform(&data[..], &mut encrypted).expect("Encryption internal error");
+                assert!(&encrypted == expected, "Prepared cipher should encrypt as the function does");
+                let mut decrypted = vec![];
+                decryptor.transform(&encrypted[..], &mut decrypted).expect("Decryption internal error");
+                assert!(decrypted[..data.len()] == data[..], "Prepared cipher decryption doesn't restore the data");
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_rounds() {
+        let data: Vec<u8> = (0..100).collect();
+        let key = 0x0E329232EA6D0D73;
+        let mut standard = vec![];
+        encrypt(&data[..], &mut standard, key, Endianess::Big)
+            .expect("Encryption internal error");
+        let mut sixteen = vec![];
+        encrypt_with_rounds(&data[..], &mut sixteen, key, Endianess::Big, 16, 1)
+            .expect("Encryption internal error");
+        assert!(standard == sixteen, "16 rounds should be the standard DES");
+        for &rounds in [4, 8, 20].iter() {
+            let mut encrypted = vec![];
+            encrypt_with_rounds(&data[..], &mut encrypted, key, Endianess::Big, rounds, 2)
+                .expect("Encryption internal error");
+            assert!(encrypted != standard, "{} rounds should differ from DES", rounds);
+            let mut decrypted = vec![];
+            decrypt_with_rounds(&encrypted[..], &mut decrypted, key, Endianess::Big, rounds, 2)
+                .expect("Decryption internal error");
+            assert!(decrypted[..data.len()] == data[..],
+                    "{} rounds decryption doesn't restore the data", rounds);
+        }
+    }
+
+    /// Output, whose reader is gone (as stdout of `des ... | head`)
+    struct ClosedOutput;
+
+    impl Write for ClosedOutput {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    #[test]
+    fn test_closed_output() {
+        // enough data for the workers and for several writes
+        let data = vec![0x5A; IO_BUF_SIZE * 8];
+        for &threads in [1, 4].iter() {
+            let cipher = PreparedCipher::des(0x0E329232EA6D0D73, true, Endianess::Big,
+                                             details::DES_ROUNDS_NUMBER, threads);
+            let error = cipher.transform(&data[..], ClosedOutput)
+                .expect_err("Writing to a closed output should fail");
+            assert!(error.kind() == io::ErrorKind::BrokenPipe,
+                    "A closed output should be reported as BrokenPipe, not {:?}", error);
+        }
+    }
 }
 
@@ -0,0 +1,234 @@
+//! Throughput measurement of DES and 3DES in ECB, CBC and CTR modes,
+//! for every engine the crate offers. Results can be reported as a
+//! human readable table, or as JSON for tracking of regressions
+
+use std::time::{Duration, Instant};
+use crate::des::api::{self, Engine, Mode};
+use crate::des::details::KeyScheduler;
+use crate::reinterpret_bytes::Endianess;
+
+const BENCH_KEY: u64 = 0x133457799BBCDFF1;
+const BENCH_TRIPLE_KEYS: (u64, u64, u64) = (BENCH_KEY, 0x0E329232EA6D0D73, 0x0123456789ABCDEF);
+const BENCH_IV: u64 = 0x1234567890ABCDEF;
+const BYTES_IN_MB: f64 = 1024.0 * 1024.0;
+const KEY_SCHEDULES_NUMBER: u32 = 1000;
+
+/// Direction of data transformation
+#[derive(Copy, Clone, Debug)]
+pub enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+impl Direction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Encrypt => "encrypt",
+            Direction::Decrypt => "decrypt",
+        }
+    }
+}
+
+/// Cipher, which is measured
+#[derive(Copy, Clone, Debug)]
+pub enum Cipher {
+    Des,
+    TripleDes,
+}
+
+impl Cipher {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Cipher::Des => "DES",
+            Cipher::TripleDes => "3DES",
+        }
+    }
+}
+
+/// A single measurement of a stream transformation
+#[derive(Clone, Debug)]
+pub struct Throughput {
+    pub cipher: &'static str,
+    pub mode: &'static str,
+    pub engine: Engine,
+    pub threads: usize,
+    pub direction: Direction,
+    pub bytes: usize,
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    /// @returns megabytes (of 2^20 bytes) transformed per second
+    pub fn mb_per_sec(&self) -> f64 {
+        self.bytes as f64 / BYTES_IN_MB / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Cost of building key schedulers, which is paid once per key
+#[derive(Clone, Debug)]
+pub struct KeyScheduleCost {
+    pub encrypting: Duration,
+    pub decrypting: Duration,
+}
+
+/// All the results of the benchmark
+#[derive(Clone, Debug)]
+pub struct BenchReport {
+    pub data_size: usize,
+    pub throughputs: Vec<Throughput>,
+    pub key_schedule: KeyScheduleCost,
+}
+
+/// Runs all the measurements on ‘data_size’ bytes of data. Threaded
+/// engine runs on ‘threads’ threads (CBC encryption is sequential, so
+/// it isn’t measured on it)
+/// @returns report with all the measurements
+pub fn run(data_size: usize, threads: usize) -> BenchReport {
+    let data = bench_data(data_size);
+    let mut configurations = vec![(Engine::Reference, 1), (Engine::Table, 1)];
+    if threads > 1 {
+        configurations.push((Engine::Table, threads));
+    }
+    let modes = [Mode::Ecb, Mode::Cbc { iv: BENCH_IV }, Mode::Ctr { counter: BENCH_IV }];
+    let mut throughputs = vec![];
+    for &cipher in [Cipher::Des, Cipher::TripleDes].iter() {
+        for &mode in modes.iter() {
+            for &(engine, threads) in configurations.iter() {
+                for &direction in [Direction::Encrypt, Direction::Decrypt].iter() {
+                    let encrypting = matches!(direction, Direction::Encrypt);
+                    if threads > 1 && !mode.is_parallel(encrypting) {
+                        continue;
+                    }
+                    throughputs.push(measure(&data, cipher, mode, engine, threads, direction));
+                }
+            }
+        }
+    }
+    BenchReport {
+        data_size,
+        throughputs,
+        key_schedule: measure_key_schedule(),
+    }
+}
+
+/// Pseudo random data, so that the measurement doesn’t depend on
+/// content too much
+fn bench_data(size: usize) -> Vec<u8> {
+    let mut state = 0x0123456789ABCDEF_u64;
+    (0..size).map(|_| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 56) as u8
+    }).collect()
+}
+
+/// Measures transformation of the whole ‘data’ stream
+/// @returns measured throughput
+fn measure(data: &[u8], cipher: Cipher, mode: Mode, engine: Engine, threads: usize,
+           direction: Direction) -> Throughput {
+    let mut output = Vec::with_capacity(data.len() + api::BYTES_IN_BLOCK);
+    let start = Instant::now();
+    match (cipher, direction) {
+        (Cipher::Des, Direction::Encrypt) => api::encrypt_in_mode(
+            data, &mut output, BENCH_KEY, mode, Endianess::Big, engine, threads),
+        (Cipher::Des, Direction::Decrypt) => api::decrypt_in_mode(
+            data, &mut output, BENCH_KEY, mode, Endianess::Big, engine, threads),
+        (Cipher::TripleDes, Direction::Encrypt) => api::triple_encrypt_in_mode(
+            data, &mut output, BENCH_TRIPLE_KEYS, mode, Endianess::Big, engine, threads),
+        (Cipher::TripleDes, Direction::Decrypt) => api::triple_decrypt_in_mode(
+            data, &mut output, BENCH_TRIPLE_KEYS, mode, Endianess::Big, engine, threads),
+    }.expect("In-memory streams don't fail");
+    Throughput {
+        cipher: cipher.name(),
+        mode: mode.name(),
+        engine,
+        threads,
+        direction,
+        bytes: data.len(),
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Measures average time of building encrypting and decrypting key
+/// schedulers
+fn measure_key_schedule() -> KeyScheduleCost {
+    let start = Instant::now();
+    for key in 0..KEY_SCHEDULES_NUMBER {
+        std::hint::black_box(KeyScheduler::new_encrypting(BENCH_KEY ^ key as u64));
+    }
+    let encrypting = start.elapsed() / KEY_SCHEDULES_NUMBER;
+    let start = Instant::now();
+    for key in 0..KEY_SCHEDULES_NUMBER {
+        std::hint::black_box(KeyScheduler::new_decrypting(BENCH_KEY ^ key as u64));
+    }
+    let decrypting = start.elapsed() / KEY_SCHEDULES_NUMBER;
+    KeyScheduleCost { encrypting, decrypting }
+}
+
+impl BenchReport {
+    /// Human readable report
+    /// @returns text table with a row per measurement
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "{:<7} {:<5} {:<10} {:>7} {:<8} {:>10}\n",
+            "cipher", "mode", "engine", "threads", "action", "MB/s");
+        for t in self.throughputs.iter() {
+            table += &format!(
+                "{:<7} {:<5} {:<10} {:>7} {:<8} {:>10.2}\n",
+                t.cipher, t.mode, t.engine.name(), t.threads,
+                t.direction.name(), t.mb_per_sec());
+        }
+        table += &format!(
+            "\nKey schedule setup: encrypting {:.2} us, decrypting {:.2} us\n",
+            self.key_schedule.encrypting.as_secs_f64() * 1e6,
+            self.key_schedule.decrypting.as_secs_f64() * 1e6);
+        table
+    }
+
+    /// Machine readable report
+    /// @returns JSON object with all the measurements
+    pub fn to_json(&self) -> String {
+        let throughputs: Vec<String> = self.throughputs.iter().map(|t| format!(
+            "    {{\"cipher\": \"{}\", \"mode\": \"{}\", \"engine\": \"{}\", \
+             \"threads\": {}, \"action\": \"{}\", \"bytes\": {}, \
+             \"seconds\": {:.6}, \"mb_per_sec\": {:.3}}}",
+            t.cipher, t.mode, t.engine.name(), t.threads, t.direction.name(),
+            t.bytes, t.elapsed.as_secs_f64(), t.mb_per_sec()
+        )).collect();
+        format!(
+            "{{\n  \"data_size\": {},\n  \"throughputs\": [\n{}\n  ],\n  \
+             \"key_schedule_us\": {{\"encrypting\": {:.3}, \"decrypting\": {:.3}}}\n}}\n",
+            self.data_size,
+            throughputs.join(",\n"),
+            self.key_schedule.encrypting.as_secs_f64() * 1e6,
+            self.key_schedule.decrypting.as_secs_f64() * 1e6,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_report() {
+        let report = run(api::BYTES_IN_BLOCK * 16, 2);
+        // 2 ciphers, 3 modes, 3 engines and 2 directions, but CBC
+        // encryption isn't threaded
+        assert_eq!(report.throughputs.len(), 34,
+                   "Every cipher, mode and engine should be measured in both directions");
+        let threaded_cbc_encryption = report.throughputs.iter().any(|t| {
+            t.mode == "CBC" && t.threads > 1 && matches!(t.direction, Direction::Encrypt)
+        });
+        assert!(!threaded_cbc_encryption, "CBC encryption can't run on threads");
+        let table = report.to_table();
+        assert!(table.contains("reference") && table.contains("table"),
+                "Table report misses engines");
+        assert!(table.contains("3DES") && table.contains("CBC") && table.contains("CTR"),
+                "Table report misses ciphers or modes");
+        let json = report.to_json();
+        assert!(json.starts_with('{') && json.trim_end().ends_with('}'),
+                "JSON report isn't an object");
+        assert_eq!(json.matches("\"engine\"").count(), 34,
+                   "JSON report should have a record per measurement");
+    }
+}
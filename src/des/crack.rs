@@ -0,0 +1,503 @@
+//! Exhaustive key search of DES by a known plaintext / ciphertext
+//! block. Part of the key can be known, then only the remaining bits
+//! are searched. The search goes on several threads, each takes chunks
+//! of keys in turn, and stops as soon as the key is found.
+//!
+//! Keys of a chunk are walked in Gray code order: the next key differs
+//! in a single bit, and since the key schedule only permutes bits,
+//! round keys are updated by xor of that bit’s round keys, instead of
+//! scheduling every key from scratch. If the ciphertext of the
+//! complemented plaintext is known too, the complementation property
+//! of DES, E(~K, ~P) = ~E(K, P), tests two keys per encryption
+
+use std::error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::des::details::{table_feilstel_function, DesKeySchedule, FeistelNetwork};
+use crate::des::details::{Key, KeyScheduler, INITIAL_PERMUTATION};
+
+const BITS_IN_KEY: u32 = 64;
+const ROUNDS_NUMBER: usize = 16;
+const ROUND_KEY_SIZE_BITS: u32 = 48;
+/// The lowest bit of every byte is a parity bit, DES ignores it
+pub const PARITY_BITS: u64 = 0x0101_0101_0101_0101;
+/// Keys are taken by threads in chunks of this size (or less, if the
+/// search is smaller)
+const CHUNK_SIZE_BITS: u32 = 16;
+/// How often searching threads check, whether the search is over
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// DES ignores parity bits, so they can’t be recovered by the search,
+/// they’re set as the standard requires: every byte of the key has an
+/// odd number of ones
+/// @returns the key with its parity bits set
+pub fn with_odd_parity(key: u64) -> u64 {
+    let mut bytes = key.to_be_bytes();
+    for byte in bytes.iter_mut() {
+        let data_bits = *byte & !1;
+        *byte = data_bits | (data_bits.count_ones() % 2 == 0) as u8;
+    }
+    u64::from_be_bytes(bytes)
+}
+
+/// Set of keys to search: bits outside of ‘unknown’ mask are taken from
+/// ‘known’. Parity bits are never searched
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct KeySpace {
+    pub known: u64,
+    pub unknown: u64,
+}
+
+impl KeySpace {
+    /// @returns key space, where parity bits of ‘unknown’ are dropped
+    pub fn new(known: u64, unknown: u64) -> Self {
+        let unknown = unknown & !PARITY_BITS;
+        Self { known: known & !unknown, unknown }
+    }
+
+    pub fn unknown_bits(&self) -> u32 {
+        self.unknown.count_ones()
+    }
+
+    /// Every key has its complement in the space, only if no key bits
+    /// (but parity ones) are known
+    pub fn is_closed_under_complement(&self) -> bool {
+        self.unknown == !PARITY_BITS
+    }
+
+    /// @returns key, whose unknown bits are the lowest bits of ‘index’
+    /// (spread over the unknown mask, from its lowest bit up)
+    pub fn key(&self, index: u64) -> u64 {
+        let mut key = self.known;
+        let mut index = index;
+        for bit in (0..BITS_IN_KEY).filter(|bit| (self.unknown >> bit) & 1 == 1) {
+            key |= (index & 1) << bit;
+            index >>= 1;
+        }
+        key
+    }
+}
+
+/// Known data of the search. ‘complement’ is the ciphertext of
+/// ‘!plaintext’, if it’s known
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct KnownBlocks {
+    pub plaintext: u64,
+    pub ciphertext: u64,
+    pub complement: Option<u64>,
+}
+
+/// Parameters of the search
+#[derive(Copy, Clone, Debug)]
+pub struct CrackConfig {
+    pub blocks: KnownBlocks,
+    pub space: KeySpace,
+    pub threads: usize,
+    /// Chunk to start from (nonzero when a search is resumed)
+    pub start_chunk: u64,
+}
+
+impl CrackConfig {
+    /// Complementation property halves the search, if both ciphertexts
+    /// are known and the key space includes complements of its keys
+    pub fn uses_complementation(&self) -> bool {
+        self.blocks.complement.is_some() && self.space.is_closed_under_complement()
+    }
+
+    /// Keys, which are walked by threads. With complementation, the
+    /// highest unknown bit is zero, the complements cover the rest
+    fn walked_bits(&self) -> u32 {
+        self.space.unknown_bits() - self.uses_complementation() as u32
+    }
+
+    fn chunk_size_bits(&self) -> u32 {
+        CHUNK_SIZE_BITS.min(self.walked_bits())
+    }
+
+    /// @returns number of chunks, the search is split into
+    pub fn chunks(&self) -> u64 {
+        1 << (self.walked_bits() - self.chunk_size_bits())
+    }
+
+    /// @returns number of keys, which are covered by a chunk
+    pub fn keys_per_chunk(&self) -> u64 {
+        (1 << self.chunk_size_bits()) << self.uses_complementation() as u32
+    }
+}
+
+/// State of a running search
+#[derive(Copy, Clone, Debug)]
+pub struct Progress {
+    /// Keys, which are known not to be the key (complements included)
+    pub tested: u64,
+    pub total: u64,
+    /// Keys, which were tested before the search was resumed
+    pub resumed: u64,
+    pub elapsed: Duration,
+    /// All the chunks before this one are searched, the search can be
+    /// resumed from it
+    pub resume_chunk: u64,
+}
+
+impl Progress {
+    /// @returns speed of the search since it was started (or resumed)
+    pub fn keys_per_sec(&self) -> f64 {
+        (self.tested - self.resumed) as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// @returns estimated time left, if anything is tested yet
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.tested == self.resumed {
+            return None;
+        }
+        let left = self.total.saturating_sub(self.tested) as f64;
+        Some(Duration::from_secs_f64(left / self.keys_per_sec()))
+    }
+}
+
+/// Outcome of the search
+#[derive(Copy, Clone, Debug)]
+pub struct CrackReport {
+    pub key: Option<u64>,
+    pub progress: Progress,
+}
+
+/// Round keys of the last tested key, updated bit by bit
+struct RoundKeys {
+    /// Round keys of every single key bit
+    of_bit: Vec<[u64; ROUNDS_NUMBER]>,
+    keys: [Key; ROUNDS_NUMBER],
+}
+
+impl RoundKeys {
+    fn new() -> Self {
+        let of_bit = (0..BITS_IN_KEY).map(|bit| {
+            let mut keys = [0; ROUNDS_NUMBER];
+            for (value, key) in keys.iter_mut().zip(KeyScheduler::new_encrypting(1 << bit)) {
+                *value = key.value;
+            }
+            keys
+        }).collect();
+        let keys = [Key { value: 0, size_bits: ROUND_KEY_SIZE_BITS }; ROUNDS_NUMBER];
+        Self { of_bit, keys }
+    }
+
+    fn set(&mut self, key: u64) {
+        for key in self.keys.iter_mut() {
+            key.value = 0;
+        }
+        for bit in (0..BITS_IN_KEY).filter(|bit| (key >> bit) & 1 == 1) {
+            self.flip(bit);
+        }
+    }
+
+    fn flip(&mut self, bit: u32) {
+        for (key, value) in self.keys.iter_mut().zip(self.of_bit[bit as usize].iter()) {
+            key.value ^= value;
+        }
+    }
+}
+
+/// Shared state of searching threads
+struct Search {
+    config: CrackConfig,
+    next_chunk: AtomicU64,
+    /// Chunk in work of every thread (‘u64::MAX’ for none)
+    in_work: Vec<AtomicU64>,
+    tested_chunks: AtomicU64,
+    is_over: AtomicBool,
+    key: Mutex<Option<u64>>,
+}
+
+impl Search {
+    fn resume_chunk(&self) -> u64 {
+        self.in_work.iter().map(|chunk| chunk.load(Ordering::SeqCst))
+            .fold(self.next_chunk.load(Ordering::SeqCst), u64::min)
+            .min(self.config.chunks())
+    }
+
+    fn progress(&self, start: Instant) -> Progress {
+        let config = &self.config;
+        let total = config.chunks() * config.keys_per_chunk();
+        let tested = config.start_chunk + self.tested_chunks.load(Ordering::SeqCst);
+        Progress {
+            tested: (tested * config.keys_per_chunk()).min(total),
+            total,
+            resumed: (config.start_chunk * config.keys_per_chunk()).min(total),
+            elapsed: start.elapsed(),
+            resume_chunk: self.resume_chunk(),
+        }
+    }
+
+    /// Takes chunks until the search is over
+    fn work(&self, thread: usize) {
+        let network = FeistelNetwork::new(table_feilstel_function, DesKeySchedule);
+        let blocks = self.config.blocks;
+        // the network has no permutations, they are applied once here
+        let plaintext = INITIAL_PERMUTATION.apply(blocks.plaintext);
+        let ciphertext = INITIAL_PERMUTATION.apply(blocks.ciphertext);
+        let complement = blocks.complement.filter(|_| self.config.uses_complementation())
+            .map(|complement| !INITIAL_PERMUTATION.apply(complement));
+        let unknown_bits: Vec<u32> = (0..BITS_IN_KEY)
+            .filter(|bit| (self.config.space.unknown >> bit) & 1 == 1).collect();
+        let chunk_size = 1u64 << self.config.chunk_size_bits();
+        let mut round_keys = RoundKeys::new();
+        while !self.is_over.load(Ordering::SeqCst) {
+            let chunk = self.next_chunk.fetch_add(1, Ordering::SeqCst);
+            if chunk >= self.config.chunks() {
+                break;
+            }
+            self.in_work[thread].store(chunk, Ordering::SeqCst);
+            let first = chunk * chunk_size;
+            let mut key = self.config.space.key(first ^ (first >> 1));
+            round_keys.set(key);
+            let last = first + chunk_size - 1;
+            for index in first..=last {
+                let output = network.act_on_block(plaintext, &mut round_keys.keys.iter().cloned());
+                let found = if output == ciphertext {
+                    Some(key)
+                } else if Some(output) == complement {
+                    Some(!key & !PARITY_BITS)
+                } else {
+                    None
+                };
+                if let Some(found) = found {
+                    *self.key.lock().unwrap() = Some(found);
+                    self.is_over.store(true, Ordering::SeqCst);
+                    return;
+                }
+                // next key in Gray code differs in the lowest set bit
+                // of the next index
+                if index != last {
+                    let bit = unknown_bits[(index + 1).trailing_zeros() as usize];
+                    key ^= 1 << bit;
+                    round_keys.flip(bit);
+                }
+            }
+            self.tested_chunks.fetch_add(1, Ordering::SeqCst);
+            self.in_work[thread].store(u64::MAX, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Searches the key, that encrypts the known plaintext into the known
+/// ciphertext. Every ‘progress_interval’ (and when the search is over)
+/// calls ‘on_progress’, e.g. to report progress or save a checkpoint
+/// @returns found key (its parity bits are set to odd parity)
+pub fn crack<F>(config: CrackConfig, progress_interval: Duration, mut on_progress: F) -> CrackReport
+where F: FnMut(&Progress) {
+    let start = Instant::now();
+    let search = Search {
+        config,
+        next_chunk: AtomicU64::new(config.start_chunk),
+        in_work: (0..config.threads.max(1)).map(|_| AtomicU64::new(u64::MAX)).collect(),
+        tested_chunks: AtomicU64::new(0),
+        is_over: AtomicBool::new(config.space.unknown_bits() == 0),
+        key: Mutex::new(None),
+    };
+    if config.space.unknown_bits() == 0 {
+        let network = FeistelNetwork::new(table_feilstel_function, DesKeySchedule);
+        let key = config.space.known;
+        if network.encrypt_block(INITIAL_PERMUTATION.apply(config.blocks.plaintext), key)
+            == INITIAL_PERMUTATION.apply(config.blocks.ciphertext) {
+            *search.key.lock().unwrap() = Some(key);
+        }
+    }
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..search.in_work.len())
+            .map(|thread| {
+                let search = &search;
+                scope.spawn(move || search.work(thread))
+            })
+            .collect();
+        let mut last_report = Instant::now();
+        while workers.iter().any(|worker| !worker.is_finished()) {
+            std::thread::sleep(POLL_INTERVAL);
+            if last_report.elapsed() >= progress_interval {
+                last_report = Instant::now();
+                on_progress(&search.progress(start));
+            }
+        }
+    });
+    let progress = search.progress(start);
+    on_progress(&progress);
+    let key = *search.key.lock().unwrap();
+    CrackReport { key: key.map(with_odd_parity), progress }
+}
+
+/// Possible ERRORS during parsing of a checkpoint
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseCheckpointError {
+    MissingField(&'static str),
+    BadValue(String),
+}
+
+impl fmt::Display for ParseCheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            ParseCheckpointError::MissingField(name) =>
+                write!(f, "<checkpoint misses '{}' field>", name),
+            ParseCheckpointError::BadValue(line) =>
+                write!(f, "<checkpoint line '{}' isn't valid>", line),
+        }
+    }
+}
+
+impl error::Error for ParseCheckpointError {}
+
+/// Saved state of a search, which can be resumed later. Saved as
+/// "name = value" lines, numbers are in hex
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Checkpoint {
+    pub blocks: KnownBlocks,
+    pub space: KeySpace,
+    pub next_chunk: u64,
+}
+
+impl Checkpoint {
+    pub fn to_text(&self) -> String {
+        let complement = self.blocks.complement
+            .map_or("-".to_string(), |c| format!("{:016X}", c));
+        format!("plaintext = {:016X}\nciphertext = {:016X}\ncomplement = {}\n\
+                 known = {:016X}\nunknown = {:016X}\nnext_chunk = {:X}\n",
+                self.blocks.plaintext, self.blocks.ciphertext, complement,
+                self.space.known, self.space.unknown, self.next_chunk)
+    }
+
+    /// @returns Result with the checkpoint, written by ‘to_text’
+    pub fn parse(text: &str) -> Result<Self, ParseCheckpointError> {
+        let mut fields = std::collections::HashMap::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let mut parts = line.splitn(2, '=').map(str::trim);
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => { fields.insert(name, value); },
+                _ => return Err(ParseCheckpointError::BadValue(line.to_string())),
+            }
+        }
+        let field = |name: &'static str| -> Result<Option<u64>, ParseCheckpointError> {
+            let value = fields.get(name).ok_or(ParseCheckpointError::MissingField(name))?;
+            if *value == "-" {
+                return Ok(None);
+            }
+            u64::from_str_radix(value, 16).map(Some)
+                .map_err(|_| ParseCheckpointError::BadValue(format!("{} = {}", name, value)))
+        };
+        let required = |name: &'static str| field(name)?
+            .ok_or_else(|| ParseCheckpointError::BadValue(format!("{} = -", name)));
+        Ok(Self {
+            blocks: KnownBlocks {
+                plaintext: required("plaintext")?,
+                ciphertext: required("ciphertext")?,
+                complement: field("complement")?,
+            },
+            space: KeySpace::new(required("known")?, required("unknown")?),
+            next_chunk: required("next_chunk")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::des::details::encrypt_block_separately;
+
+    const KEY: u64 = 0x133457799BBCDFF1;
+    const PLAINTEXT: u64 = 0x0123456789ABCDEF;
+
+    fn blocks(complement: bool) -> KnownBlocks {
+        KnownBlocks {
+            plaintext: PLAINTEXT,
+            ciphertext: encrypt_block_separately(PLAINTEXT, KEY),
+            complement: if complement {
+                Some(encrypt_block_separately(!PLAINTEXT, KEY))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn config(blocks: KnownBlocks, space: KeySpace, threads: usize) -> CrackConfig {
+        CrackConfig { blocks, space, threads, start_chunk: 0 }
+    }
+
+    #[test]
+    fn test_odd_parity() {
+        assert_eq!(with_odd_parity(KEY), KEY, "Textbook key has odd parity");
+        assert_eq!(with_odd_parity(KEY & !PARITY_BITS), KEY, "Parity bits are restored");
+        assert_eq!(with_odd_parity(0), PARITY_BITS);
+    }
+
+    #[test]
+    fn test_key_space() {
+        let space = KeySpace::new(KEY, 0xFF00);
+        assert_eq!(space.unknown_bits(), 7, "Parity bit isn't searched");
+        assert_eq!(space.key(0), KEY & !0xFE00, "Unknown bits are zero");
+        assert_eq!(space.key(0x7F) | space.known, KEY | 0xFE00, "Unknown bits are ones");
+        assert!(!space.is_closed_under_complement());
+        assert!(KeySpace::new(0, !0).is_closed_under_complement());
+    }
+
+    #[test]
+    fn test_crack_restricted_space() {
+        // 20 unknown bits, spread over the key
+        let space = KeySpace::new(KEY, 0x00F0_0F00_FF00_00F0);
+        for &threads in [1, 3].iter() {
+            let report = crack(config(blocks(false), space, threads), Duration::from_secs(60), |_| {});
+            assert_eq!(report.key, Some(KEY), "Key should be found on {} threads", threads);
+            assert!(report.progress.tested <= report.progress.total);
+        }
+        let wrong = KnownBlocks { ciphertext: !blocks(false).ciphertext, ..blocks(false) };
+        let mut calls = 0;
+        let report = crack(config(wrong, space, 2), Duration::from_secs(60), |_| calls += 1);
+        assert_eq!(report.key, None, "No key encrypts into a wrong ciphertext");
+        assert_eq!(report.progress.tested, report.progress.total, "The space is exhausted");
+        assert_eq!(report.progress.resume_chunk, config(wrong, space, 2).chunks());
+        assert!(calls >= 1, "Progress is reported at least in the end");
+    }
+
+    #[test]
+    fn test_complementation() {
+        // the key has the highest bit set, so it's in the half of the
+        // space, which isn't walked, and it's found by its complement
+        let key = KEY | (1 << 63);
+        let blocks = KnownBlocks {
+            plaintext: PLAINTEXT,
+            ciphertext: encrypt_block_separately(PLAINTEXT, key),
+            complement: Some(encrypt_block_separately(!PLAINTEXT, key)),
+        };
+        let mut full = config(blocks, KeySpace::new(0, !0), 2);
+        assert!(full.uses_complementation());
+        assert_eq!(full.chunks() * full.keys_per_chunk(), 1 << 56, "Whole space is covered");
+        // resume right from the chunk with the complement of the key:
+        // its index is the inverse Gray code of its unknown bits
+        let complement = !key & !PARITY_BITS;
+        let code = (0..BITS_IN_KEY).filter(|bit| (PARITY_BITS >> bit) & 1 == 0)
+            .enumerate()
+            .fold(0u64, |code, (i, bit)| code | ((complement >> bit) & 1) << i);
+        let index = (0..BITS_IN_KEY).fold(0, |index, shift| index ^ (code >> shift));
+        full.start_chunk = index >> CHUNK_SIZE_BITS;
+        let report = crack(full, Duration::from_secs(60), |_| {});
+        assert_eq!(report.key, Some(with_odd_parity(key)), "Key is found by its complement");
+
+        let restricted = config(blocks, KeySpace::new(key, 0xFF), 1);
+        assert!(!restricted.uses_complementation(), "Known bits break the symmetry");
+        assert_eq!(crack(restricted, Duration::from_secs(60), |_| {}).key, Some(with_odd_parity(key)));
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let checkpoint = Checkpoint {
+            blocks: blocks(true),
+            space: KeySpace::new(KEY, 0xFFFF_0000),
+            next_chunk: 0x1A,
+        };
+        assert_eq!(Checkpoint::parse(&checkpoint.to_text()), Ok(checkpoint.clone()));
+        let without = Checkpoint { blocks: blocks(false), ..checkpoint };
+        assert_eq!(Checkpoint::parse(&without.to_text()), Ok(without));
+        assert_eq!(Checkpoint::parse("plaintext = 1\n"),
+                   Err(ParseCheckpointError::MissingField("ciphertext")));
+        assert!(Checkpoint::parse("next_chunk = zz\nplaintext = 1\n").is_err());
+    }
+}
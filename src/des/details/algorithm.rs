@@ -1,37 +1,32 @@
-use super::{encrypt_round, encrypt_last_round, KeyScheduler, DecryptingKeyScheduler, EncryptingKeyScheduler, Key, PermutationTable};
+use super::{table_act_on_block, table_feilstel_function, feilstel_function, KeyScheduler, DecryptingKeyScheduler, EncryptingKeyScheduler, Key, PermutationTable};
+use super::feistel::{FeistelNetwork, DesKeySchedule, DES_ROUNDS_NUMBER};
 
 const PERMUTATION_INPUT_SIZE: u32 = 64;
 const BIT_COUNT_FROM: u32 = 1;
 
 /// The permutation of data block before any other encrypting / decrypting 
-lazy_static! {
-    static ref INITIAL_PERMUTATION: PermutationTable
-    = PermutationTable::new(vec![
-        58, 50, 42, 34, 26, 18, 10, 2,
-        60, 52, 44, 36, 28, 20, 12, 4,
-        62, 54, 46, 38, 30, 22, 14, 6,
-        64, 56, 48, 40, 32, 24, 16, 8,
-        57, 49, 41, 33, 25, 17,  9, 1,
-        59, 51, 43, 35, 27, 19, 11, 3,
-        61, 53, 45, 37, 29, 21, 13, 5,
-        63, 55, 47, 39, 31, 23, 15, 7,
-    ], BIT_COUNT_FROM, PERMUTATION_INPUT_SIZE);
-}
+pub(crate) static INITIAL_PERMUTATION: PermutationTable = PermutationTable::new(&[
+    58, 50, 42, 34, 26, 18, 10, 2,
+    60, 52, 44, 36, 28, 20, 12, 4,
+    62, 54, 46, 38, 30, 22, 14, 6,
+    64, 56, 48, 40, 32, 24, 16, 8,
+    57, 49, 41, 33, 25, 17,  9, 1,
+    59, 51, 43, 35, 27, 19, 11, 3,
+    61, 53, 45, 37, 29, 21, 13, 5,
+    63, 55, 47, 39, 31, 23, 15, 7,
+], BIT_COUNT_FROM, PERMUTATION_INPUT_SIZE);
 
 /// The permutation of data block after all encrypting / decrypting 
-lazy_static! {
-    static ref REVERSE_PERMUTATION: PermutationTable
-    = PermutationTable::new(vec![
-        40, 8, 48, 16, 56, 24, 64, 32,
-        39, 7, 47, 15, 55, 23, 63, 31,
-        38, 6, 46, 14, 54, 22, 62, 30,
-        37, 5, 45, 13, 53, 21, 61, 29,
-        36, 4, 44, 12, 52, 20, 60, 28,
-        35, 3, 43, 11, 51, 19, 59, 27,
-        34, 2, 42, 10, 50, 18, 58, 26,
-        33, 1, 41,  9, 49, 17, 57, 25,
-    ], BIT_COUNT_FROM, PERMUTATION_INPUT_SIZE);
-}
+pub(crate) static REVERSE_PERMUTATION: PermutationTable = PermutationTable::new(&[
+    40, 8, 48, 16, 56, 24, 64, 32,
+    39, 7, 47, 15, 55, 23, 63, 31,
+    38, 6, 46, 14, 54, 22, 62, 30,
+    37, 5, 45, 13, 53, 21, 61, 29,
+    36, 4, 44, 12, 52, 20, 60, 28,
+    35, 3, 43, 11, 51, 19, 59, 27,
+    34, 2, 42, 10, 50, 18, 58, 26,
+    33, 1, 41,  9, 49, 17, 57, 25,
+], BIT_COUNT_FROM, PERMUTATION_INPUT_SIZE);
 
 /// Takes a piece of data of size 64 bit, encrypts it with DES
 /// algorithm using a given key (making whole cycle of scheduling aswell)
@@ -74,19 +69,56 @@ pub fn decrypt_block(data: u64, scheduler: &mut impl Iterator<Item=Key>) -> u64
     )
 }
 
+/// Takes a piece of data of size 64 bit, encrypts it with DES
+/// algorithm, using a given key scheduler. Uses precomputed tables
+/// instead of bit by bit permutations, but yields the same result as
+/// ‘encrypt_block’
+/// @returns encrypted piece of data
+pub fn encrypt_block_table(data: u64, scheduler: &mut impl Iterator<Item=Key>) -> u64 {
+    table_act_on_block(
+        data,
+        scheduler,
+    )
+}
+
+/// Takes a piece of data of size 64 bit, decrypts it with DES
+/// algorithm, using a given key scheduler. Uses precomputed tables
+/// instead of bit by bit permutations, but yields the same result as
+/// ‘decrypt_block’
+/// @returns decrypted piece of data
+pub fn decrypt_block_table(data: u64, scheduler: &mut impl Iterator<Item=Key>) -> u64 {
+    table_act_on_block(
+        data,
+        scheduler,
+    )
+}
+
+/// DES as a Feistel network with a given number of rounds (DES
+/// specifies 16, but reduced or extended variants are possible)
+/// @returns table-optimized DES network
+pub fn des_network(rounds: usize)
+                   -> FeistelNetwork<'static, impl Fn(u32, Key) -> u32 + Copy + Send + Sync, DesKeySchedule> {
+    FeistelNetwork::new(table_feilstel_function, DesKeySchedule)
+        .rounds(rounds)
+        .permutations(&INITIAL_PERMUTATION, &REVERSE_PERMUTATION)
+}
+
+/// The same as ‘des_network’, but the round function goes bit by bit
+/// @returns reference DES network
+pub fn reference_des_network(rounds: usize)
+                             -> FeistelNetwork<'static, impl Fn(u32, Key) -> u32 + Copy + Send + Sync, DesKeySchedule> {
+    FeistelNetwork::new(feilstel_function, DesKeySchedule)
+        .rounds(rounds)
+        .permutations(&INITIAL_PERMUTATION, &REVERSE_PERMUTATION)
+}
+
 /// Since DES encryption and decrytion algorithms differ only in key
 /// scheduling, they both can be implemented with this function
 /// @returns encrypted / decrypted piece of data, depending on keys given
 #[inline]
-fn act_on_block<I>(mut data: u64, key_iterator: &mut I) -> u64
+fn act_on_block<I>(data: u64, key_iterator: &mut I) -> u64
 where I: Iterator<Item=Key>{
-    const ROUNDS_NUMBER: usize = 16;
-    data = INITIAL_PERMUTATION.apply(data);
-    for (_, round_key) in (0..ROUNDS_NUMBER-1).zip(key_iterator.by_ref()) {
-        data = encrypt_round(data, round_key);
-    }
-    data = encrypt_last_round(data, key_iterator.next().unwrap());
-    REVERSE_PERMUTATION.apply(data)
+    reference_des_network(DES_ROUNDS_NUMBER).act_on_block(data, key_iterator)
 }
 
 #[cfg(test)]
@@ -117,6 +149,26 @@ mod tests {
             "Encryption test 4"
         );
     }
+    #[test]
+    fn test_table_engine_matches_reference() {
+        let mut data = 0x0123456789ABCDEF_u64;
+        let mut key = 0x133457799BBCDFF1_u64;
+        for _ in 0..256 {
+            let mut reference = KeyScheduler::new_encrypting(key);
+            let mut table = KeyScheduler::new_encrypting(key);
+            let encrypted = encrypt_block(data, &mut reference);
+            assert_eq!(encrypt_block_table(data, &mut table), encrypted,
+                       "Table-optimized encryption differs from the reference one");
+            let mut reference = KeyScheduler::new_decrypting(key);
+            let mut table = KeyScheduler::new_decrypting(key);
+            assert_eq!(decrypt_block_table(encrypted, &mut table),
+                       decrypt_block(encrypted, &mut reference),
+                       "Table-optimized decryption differs from the reference one");
+            data = data.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            key = key.rotate_left(7) ^ data;
+        }
+    }
+
     #[test]
     fn test_decryption_of_block() {
         assert_eq!(
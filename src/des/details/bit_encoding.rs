@@ -1,49 +1,89 @@
-use crate::math::bit_arithmetics::idx_from_low as low;
-use crate::math::bit_arithmetics::idx_from_high as high;
-
-const MAX_INT_SIZE_BITS: u32 = 64;
+const MAX_INPUT_SIZE_BITS: u32 = 6;
+const MAX_ENCODING_RULES: usize = 1 << MAX_INPUT_SIZE_BITS;
+const MAX_OUTPUT_SIZE_BITS: u32 = 64;
 
 /// Encoding table splits the number into pieces of certain size, each
 /// piece will be looked up in the table and associated with a new
-/// value. These values will be grouped into a new number
+/// value. These values will be grouped into a new number. Can be
+/// built at compile time, then it’s validated by the compiler aswell
 pub struct EncodingTable<'a> {
-    encoding_rules: Vec<u64>,
+    encoding_rules: [u64; MAX_ENCODING_RULES],
     input_size: u32,
     output_size: u32,
     row_bits: &'a [u32],
 }
 
 impl<'a> EncodingTable<'a> {
-    pub fn new(encoding_rules: Vec<u64>, row_bits: &'a [u32],
-               input_size: u32, output_size: u32) -> EncodingTable {
-        // TODO: return result, check inputs
-        // TODO: start bit counting argument (from 1?)
+    /// Fills the table with given rules. ‘row_bits’ are ascending
+    /// indices of input bits (from the highest, starting from 0),
+    /// which select a row of the table, the rest of bits select a
+    /// column. Panics (or fails to compile, if built in constant
+    /// context) if there isn’t a rule for every input, or some rule
+    /// doesn’t fit in output size
+    /// @returns constructed table
+    pub const fn new(encoding_rules: &[u64], row_bits: &'a [u32],
+                     input_size: u32, output_size: u32) -> EncodingTable<'a> {
+        assert!(input_size <= MAX_INPUT_SIZE_BITS,
+                "Encoding table input is wider than 6 bits");
+        assert!(output_size <= MAX_OUTPUT_SIZE_BITS,
+                "Encoding table output is wider than 64 bits");
+        assert!(encoding_rules.len() == 1 << input_size,
+                "Encoding table should have a rule for every input");
+        let mut rules = [0; MAX_ENCODING_RULES];
+        let mut idx = 0;
+        while idx < encoding_rules.len() {
+            assert!(output_size == MAX_OUTPUT_SIZE_BITS
+                    || encoding_rules[idx] >> output_size == 0,
+                    "Encoding rule doesn't fit in output size");
+            rules[idx] = encoding_rules[idx];
+            idx += 1;
+        }
+        let mut idx = 0;
+        while idx < row_bits.len() {
+            assert!(row_bits[idx] < input_size,
+                    "Row bit is out of encoding table input");
+            assert!(idx == 0 || row_bits[idx - 1] < row_bits[idx],
+                    "Row bits should be ascending");
+            idx += 1;
+        }
         EncodingTable {
-            encoding_rules,
+            encoding_rules: rules,
             input_size,
             output_size,
             row_bits,
         }
     }
+
     /// splits the number into pieces of certain size, each
     /// piece will be looked up in the table and associated with a new
     /// value. These values will be grouped into a result number
     /// @returns result of encoding (with stated rules)
-    pub fn apply(&self, number: u64) -> u64 {
-        let total_columns = (self.encoding_rules.len() / (1 << self.row_bits.len())) as u64;
-        let (col_idx, _) = high::drop_bits(number, self.row_bits.iter().cloned(), self.input_size);
-        let row_idx = self.row_bits.iter().fold(
-            0, |acc, &bit| (acc + high::get_bit(number, bit, self.input_size)) << 1
-        ) >> 1;
+    #[inline]
+    pub const fn apply(&self, number: u64) -> u64 {
+        let total_columns = (1 << self.input_size) >> self.row_bits.len();
+        let (mut row_idx, mut col_idx) = (0, 0);
+        let (mut bit, mut next_row_bit) = (0, 0);
+        while bit < self.input_size {
+            let value = (number >> (self.input_size - bit - 1)) & 1;
+            if next_row_bit < self.row_bits.len() && self.row_bits[next_row_bit] == bit {
+                row_idx = (row_idx << 1) | value;
+                next_row_bit += 1;
+            } else {
+                col_idx = (col_idx << 1) | value;
+            }
+            bit += 1;
+        }
         self.encoding_rules[(row_idx * total_columns + col_idx) as usize]
     }
 }
 
 /// Field access functions
 impl<'e> EncodingTable<'e> {
-    pub fn input_size(&'e self) -> u32 { self.input_size }
-    pub fn output_size(&'e self) -> u32 { self.output_size }
-    pub fn encoding_rules(&'e self) -> &Vec<u64> { &self.encoding_rules }
+    pub const fn input_size(&self) -> u32 { self.input_size }
+    pub const fn output_size(&self) -> u32 { self.output_size }
+    pub fn encoding_rules(&self) -> &[u64] {
+        &self.encoding_rules[..1 << self.input_size]
+    }
 }
 
 #[cfg(test)]
@@ -53,7 +93,7 @@ mod tests {
     #[test]
     pub fn test_encoding_table() {
         let table = EncodingTable::new(
-            vec![
+            &[
                 14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7,
                 0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8,
                 4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0,
@@ -64,7 +104,7 @@ mod tests {
         assert_eq!(table.apply(0b101001), 4, "Test 2");
         assert_eq!(table.apply(0b001101), 13, "Test 3");
         let table = EncodingTable::new(
-            vec![
+            &[
                 15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10,
                 3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1, 10, 6, 9, 11, 5,
                 0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15,
@@ -75,4 +115,9 @@ mod tests {
         assert_eq!(table.apply(0b100101), 10, "Test 5");
     }
 
+    #[test]
+    #[should_panic(expected = "rule for every input")]
+    pub fn test_encoding_table_validation() {
+        EncodingTable::new(&[0, 1, 2], &[0, 1], 2, 2);
+    }
 }
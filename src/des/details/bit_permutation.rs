@@ -1,58 +1,136 @@
-use crate::math::bit_arithmetics::idx_from_high as high;
 use crate::math::bit_arithmetics::idx_from_low as low;
 
-/// Bit permutation table. Allowes to permute bits in a number in any order
+const BITS_IN_BYTE: u32 = 8;
+const BYTE_VALUES: usize = 1 << BITS_IN_BYTE;
+const MAX_SIZE_BITS: usize = 64;
+const MAX_SIZE_BYTES: usize = MAX_SIZE_BITS / BITS_IN_BYTE as usize;
+
+/// Bit permutation table. Allowes to permute bits in a number in any
+/// order. Can be built at compile time, then it’s validated by the
+/// compiler aswell
 pub struct PermutationTable{
     input_size: u32,
     output_size: u32,
-    bit_destinations: Vec<u32>,
+    bit_destinations: [u32; MAX_SIZE_BITS],
+    // permuted value of each possible byte of the input, starting
+    // from the lowest byte
+    byte_masks: [[u64; BYTE_VALUES]; MAX_SIZE_BYTES],
 }
 
 impl PermutationTable {
-    /// Fills the table with given values, fixes ordering to uniform
+    /// Fills the table with given values, fixes ordering to uniform.
+    /// Panics (or fails to compile, if built in constant context) if
+    /// the table is wider than 64 bits, or refers to bits out of input
     /// @returns constructed table
-    pub fn new(mut bit_destinations: Vec<u32>, numbering_from: u32, input_size_bits: u32) -> Self {
-        for idx in bit_destinations.iter_mut(){
-            *idx -= numbering_from;
+    pub const fn new(bit_destinations: &[u32], numbering_from: u32, input_size_bits: u32) -> Self {
+        assert!(input_size_bits as usize <= MAX_SIZE_BITS,
+                "Permutation input is wider than 64 bits");
+        assert!(bit_destinations.len() <= MAX_SIZE_BITS,
+                "Permutation output is wider than 64 bits");
+        let mut destinations = [0; MAX_SIZE_BITS];
+        let mut idx = 0;
+        while idx < bit_destinations.len() {
+            assert!(bit_destinations[idx] >= numbering_from
+                    && bit_destinations[idx] - numbering_from < input_size_bits,
+                    "Permutation refers to a bit out of input");
+            destinations[idx] = bit_destinations[idx] - numbering_from;
+            idx += 1;
         }
-        Self {
+        let mut table = Self {
             input_size: input_size_bits,
             output_size: bit_destinations.len() as u32,
-            bit_destinations,
-        }
+            bit_destinations: destinations,
+            byte_masks: [[0; BYTE_VALUES]; MAX_SIZE_BYTES],
+        };
+        table.byte_masks = table.compile();
+        table
     }
 
     /// Permutes bits in the given number in order, that is specified
     /// in the table. 0th bit in the number will go in table[0] place
-    /// in the result
+    /// in the result. Every input byte is looked up in the compiled
+    /// table, and the lookups are merged
     /// @returns permuted number
-    pub fn apply(&self, value: u64) -> u64 {
+    #[inline]
+    pub const fn apply(&self, value: u64) -> u64 {
+        let input_bytes = self.input_size.div_ceil(BITS_IN_BYTE) as usize;
         let mut result = 0;
-        if self.bit_destinations.len() == 0 { return 0; }
-        let loop_limit = std::cmp::min(self.bit_destinations.len() - 1, 63);
-        for &bit_dest in self.bit_destinations[..loop_limit].iter() {
-            result += high::get_bit(value, bit_dest, self.input_size);
-            result <<= 1;
+        let mut byte_idx = 0;
+        while byte_idx < input_bytes {
+            let byte = (value >> (byte_idx as u32 * BITS_IN_BYTE)) as u8;
+            result |= self.byte_masks[byte_idx][byte as usize];
+            byte_idx += 1;
         }
-        result + high::get_bit(
-            value, self.bit_destinations[loop_limit], self.input_size)
+        result
+    }
+
+    /// Precomputes the permutation of every value of every byte of the
+    /// input (which is ‘input_size’ wide), so that applying the table
+    /// costs a single lookup per byte
+    /// @returns tables of permuted values, starting from the lowest byte
+    const fn compile(&self) -> [[u64; BYTE_VALUES]; MAX_SIZE_BYTES] {
+        // where each input bit (from the lowest) goes in the output
+        let mut bit_masks = [0u64; MAX_SIZE_BITS];
+        let mut idx = 0;
+        while idx < self.output_size as usize {
+            let source = self.input_size - self.bit_destinations[idx] - 1;
+            bit_masks[source as usize] |= 1 << (self.output_size as usize - idx - 1);
+            idx += 1;
+        }
+        let mut byte_masks = [[0; BYTE_VALUES]; MAX_SIZE_BYTES];
+        let mut byte_idx = 0;
+        while byte_idx < MAX_SIZE_BYTES {
+            let mut byte = 0;
+            while byte < BYTE_VALUES {
+                let mut bit = 0;
+                while bit < BITS_IN_BYTE as usize {
+                    if (byte >> bit) & 1 == 1 {
+                        byte_masks[byte_idx][byte] |=
+                            bit_masks[byte_idx * BITS_IN_BYTE as usize + bit];
+                    }
+                    bit += 1;
+                }
+                byte += 1;
+            }
+            byte_idx += 1;
+        }
+        byte_masks
     }
 }
 
 /// Access function to the fields
 impl PermutationTable {
-    pub fn input_size(&self) -> u32 { self.input_size }
-    pub fn output_size(&self) -> u32 { self.output_size }
-    pub fn bit_destinations(&self) -> &Vec<u32> { &self.bit_destinations }
+    pub const fn input_size(&self) -> u32 { self.input_size }
+    pub const fn output_size(&self) -> u32 { self.output_size }
+    pub fn bit_destinations(&self) -> &[u32] {
+        &self.bit_destinations[..self.output_size as usize]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::bit_arithmetics::idx_from_high as high;
+
+    /// Reference for ‘PermutationTable::apply’: permutes bits one by
+    /// one, walking through the table
+    /// @returns permuted number
+    fn apply_bitwise(table: &PermutationTable, value: u64) -> u64 {
+        let bit_destinations = table.bit_destinations();
+        let mut result = 0;
+        if bit_destinations.is_empty() { return 0; }
+        let loop_limit = std::cmp::min(bit_destinations.len() - 1, 63);
+        for &bit_dest in bit_destinations[..loop_limit].iter() {
+            result += high::get_bit(value, bit_dest, table.input_size());
+            result <<= 1;
+        }
+        result + high::get_bit(
+            value, bit_destinations[loop_limit], table.input_size())
+    }
 
     #[test]
     pub fn test_permutation_table() {
-        let pt = PermutationTable::new(vec![
+        let pt = PermutationTable::new(&[
             57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18,
             10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60, 52, 44, 36,
             63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22,
@@ -64,7 +142,7 @@ mod tests {
             "Permutation table doesn't work correctly"
         );
 
-        let pt = PermutationTable::new(vec![
+        let pt = PermutationTable::new(&[
             58, 50, 42, 34, 26, 18, 10, 2,
             60, 52, 44, 36, 28, 20, 12, 4,
             62, 54, 46, 38, 30, 22, 14, 6,
@@ -75,8 +153,8 @@ mod tests {
             63, 55, 47, 39, 31, 23, 15, 7,
         ], 1, 64);
         assert_eq!(
-            pt.apply(std::u64::MAX),
-            std::u64::MAX,
+            pt.apply(u64::MAX),
+            u64::MAX,
             "Permutation of all 1's number doesn't yield all 1's number"
         );
         assert_eq!(
@@ -85,5 +163,31 @@ mod tests {
             "Permutation table doesn't work correctly #2");
         
     }
-    
+
+    #[test]
+    pub fn test_compiled_permutation_table() {
+        let pt = PermutationTable::new(&[
+            32, 1, 2, 3, 4, 5,
+            4, 5, 6, 7, 8, 9,
+            8, 9, 10, 11, 12, 13,
+            12, 13, 14, 15, 16, 17,
+            16, 17, 18, 19, 20, 21,
+            20, 21, 22, 23, 24, 25,
+            24, 25, 26, 27, 28, 29,
+            28, 29, 30, 31, 32, 1,
+        ], 1, 32);
+        let mut value = 0xF0AAF0AA_u64;
+        for _ in 0..1000 {
+            assert_eq!(pt.apply(value), apply_bitwise(&pt, value),
+                       "Compiled permutation differs from bit by bit one");
+            value = value.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407)
+                & 0xFFFF_FFFF;
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of input")]
+    pub fn test_permutation_table_validation() {
+        PermutationTable::new(&[1, 2, 33], 1, 32);
+    }
 }
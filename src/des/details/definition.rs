@@ -0,0 +1,399 @@
+//! DES-like cipher, which tables are loaded from a text definition
+//! (see bin/des.def for the definition of DES itself). The structure is
+//! fixed: Feistel network over 32 bit halves (of any number of rounds,
+//! 16 as in DES by default), expansion to 48 bits, 8 S-boxes of 6 to 4
+//! bits and key schedule of DES with 16 rotations, but every table can
+//! be replaced. The tables are validated, so that the cipher
+//! is still invertible and its S-boxes are balanced
+
+use std::error;
+use std::fmt;
+use crate::math::bit_arithmetics::idx_from_low as low;
+use super::{EncodingTable, FeistelNetwork, Key, KeySchedule, PermutationTable};
+use super::trace::{self, BlockTrace, KeyRoundTrace, KeyScheduleTrace, TracedTables};
+
+const BIT_COUNT_FROM: u32 = 1;
+const BITS_IN_BLOCK: u32 = 64;
+const BITS_IN_HALF: u32 = 32;
+const BITS_IN_EXPANDED_HALF: u32 = 48;
+const BITS_IN_INNER_KEY: u32 = 56;
+const BITS_IN_ROUND_KEY: u32 = 48;
+const SBOXES_NUMBER: usize = 8;
+const SBOX_INPUT_BITS: u32 = 6;
+const SBOX_OUTPUT_BITS: u32 = 4;
+const SBOX_ROWS: usize = 4;
+const SBOX_COLUMNS: usize = 1 << SBOX_OUTPUT_BITS;
+const SBOX_ROW_BITS_INDICES: [u32; 2] = [0, 5];
+const ROTATIONS_NUMBER: usize = 16;
+
+/// Names of the sections of a definition, in the order of the file
+const SECTIONS: [&str; 8] = ["ip", "fp", "expansion", "p", "pc1", "pc2", "rotations", "sboxes"];
+
+/// Error of parsing or validation of a cipher definition
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseCipherDefError {
+    UnknownSection(String),
+    RepeatedSection(&'static str),
+    MissingSection(&'static str),
+    NotANumber(String),
+    OutsideSection(String),
+    BadLength { section: &'static str, count: usize, expected: usize },
+    OutOfRange { section: &'static str, value: u64 },
+    RepeatedBit { section: &'static str, bit: u64 },
+    NotInverse,
+    UnbalancedSBox { sbox: usize, row: usize },
+}
+
+impl fmt::Display for ParseCipherDefError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            ParseCipherDefError::UnknownSection(name) =>
+                write!(f, "<Unknown section [{}], expected one of: {}>", name, SECTIONS.join(", ")),
+            ParseCipherDefError::RepeatedSection(name) =>
+                write!(f, "<Section [{}] is given twice>", name),
+            ParseCipherDefError::MissingSection(name) =>
+                write!(f, "<Section [{}] is missing>", name),
+            ParseCipherDefError::NotANumber(word) =>
+                write!(f, "<Entry '{}' isn't a number>", word),
+            ParseCipherDefError::OutsideSection(word) =>
+                write!(f, "<Entry '{}' is before any section>", word),
+            ParseCipherDefError::BadLength { section, count, expected } =>
+                write!(f, "<{} entries in [{}], expected {}>", count, section, expected),
+            ParseCipherDefError::OutOfRange { section, value } =>
+                write!(f, "<Entry {} of [{}] is out of range>", value, section),
+            ParseCipherDefError::RepeatedBit { section, bit } =>
+                write!(f, "<Bit {} is taken twice in [{}]>", bit, section),
+            ParseCipherDefError::NotInverse =>
+                write!(f, "<[fp] isn't the inverse of [ip]>"),
+            ParseCipherDefError::UnbalancedSBox { sbox, row } =>
+                write!(f, "<Row {} of S{} doesn't have every output once>", row, sbox + 1),
+        }
+    }
+}
+
+impl error::Error for ParseCipherDefError {}
+
+/// Tables of a DES-like cipher
+pub struct CipherDefinition {
+    initial_permutation: PermutationTable,
+    final_permutation: PermutationTable,
+    expansion: PermutationTable,
+    permutation: PermutationTable,
+    key_permutation: PermutationTable,
+    permuting_choice: PermutationTable,
+    rotations: [u32; ROTATIONS_NUMBER],
+    sboxes: Vec<EncodingTable<'static>>,
+}
+
+impl CipherDefinition {
+    /// Parses a definition from a text, where every table is a section,
+    /// which starts with its name in brackets (e.g. [ip]). Numbers are
+    /// separated with whitespace or commas, hex numbers start with 0x,
+    /// everything after ’#’ on a line is a comment
+    /// @returns Result with validated definition
+    pub fn parse(text: &str) -> Result<Self, ParseCipherDefError> {
+        let mut sections: [Option<Vec<u64>>; SECTIONS.len()] = Default::default();
+        let mut current = None;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+                let idx = SECTIONS.iter().position(|&s| s == name)
+                    .ok_or_else(|| ParseCipherDefError::UnknownSection(name.to_string()))?;
+                if sections[idx].is_some() {
+                    return Err(ParseCipherDefError::RepeatedSection(SECTIONS[idx]));
+                }
+                sections[idx] = Some(vec![]);
+                current = Some(idx);
+                continue;
+            }
+            for word in line.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|w| !w.is_empty()) {
+                let value = match word.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => word.parse(),
+                }.map_err(|_| ParseCipherDefError::NotANumber(word.to_string()))?;
+                // numbers before the first section belong nowhere
+                let idx = current.ok_or_else(|| ParseCipherDefError::OutsideSection(word.to_string()))?;
+                sections[idx].as_mut().unwrap().push(value);
+            }
+        }
+        let mut entries = SECTIONS.iter().zip(sections.iter_mut())
+            .map(|(&name, entries)| entries.take().ok_or(ParseCipherDefError::MissingSection(name)));
+        let mut next = || entries.next().unwrap();
+        let (ip, fp, expansion, p) = (next()?, next()?, next()?, next()?);
+        let (pc1, pc2, rotations, sboxes) = (next()?, next()?, next()?, next()?);
+
+        let initial_permutation = permutation_table("ip", &ip, BITS_IN_BLOCK, BITS_IN_BLOCK, true)?;
+        let final_permutation = permutation_table("fp", &fp, BITS_IN_BLOCK, BITS_IN_BLOCK, true)?;
+        // the same network decrypts, only if the block is restored
+        // between the permutations
+        if (0..BITS_IN_BLOCK).any(|bit| final_permutation.apply(initial_permutation.apply(1 << bit)) != 1 << bit) {
+            return Err(ParseCipherDefError::NotInverse);
+        }
+        let expansion = permutation_table(
+            "expansion", &expansion, BITS_IN_HALF, BITS_IN_EXPANDED_HALF, false)?;
+        let permutation = permutation_table("p", &p, BITS_IN_HALF, BITS_IN_HALF, true)?;
+        let key_permutation = permutation_table("pc1", &pc1, BITS_IN_BLOCK, BITS_IN_INNER_KEY, true)?;
+        let permuting_choice = permutation_table(
+            "pc2", &pc2, BITS_IN_INNER_KEY, BITS_IN_ROUND_KEY, true)?;
+
+        check_length("rotations", &rotations, ROTATIONS_NUMBER)?;
+        let mut rotation_table = [0; ROTATIONS_NUMBER];
+        for (rotation, &value) in rotation_table.iter_mut().zip(rotations.iter()) {
+            if value >= (BITS_IN_INNER_KEY / 2) as u64 {
+                return Err(ParseCipherDefError::OutOfRange { section: "rotations", value });
+            }
+            *rotation = value as u32;
+        }
+
+        check_length("sboxes", &sboxes, SBOXES_NUMBER * SBOX_ROWS * SBOX_COLUMNS)?;
+        if let Some(&value) = sboxes.iter().find(|&&value| value >= SBOX_COLUMNS as u64) {
+            return Err(ParseCipherDefError::OutOfRange { section: "sboxes", value });
+        }
+        for (idx, row) in sboxes.chunks(SBOX_COLUMNS).enumerate() {
+            let outputs = row.iter().fold(0u32, |outputs, &value| outputs | (1 << value));
+            if outputs.count_ones() != SBOX_COLUMNS as u32 {
+                return Err(ParseCipherDefError::UnbalancedSBox {
+                    sbox: idx / SBOX_ROWS, row: idx % SBOX_ROWS });
+            }
+        }
+        let sboxes = sboxes.chunks(SBOX_ROWS * SBOX_COLUMNS)
+            .map(|rules| EncodingTable::new(
+                rules, &SBOX_ROW_BITS_INDICES, SBOX_INPUT_BITS, SBOX_OUTPUT_BITS))
+            .collect();
+
+        Ok(Self {
+            initial_permutation,
+            final_permutation,
+            expansion,
+            permutation,
+            key_permutation,
+            permuting_choice,
+            rotations: rotation_table,
+            sboxes,
+        })
+    }
+
+    /// Obscuring function of the defined cipher, operating on 32 bit
+    /// piece of data with a round key
+    /// @returns obscured with the key piece of data
+    #[inline]
+    pub fn feistel_function(&self, data: u32, key: Key) -> u32 {
+        let mixed = self.expansion.apply(data as u64) ^ key.value;
+        let merged = self.sboxes.iter().enumerate().fold(0, |merged, (idx, sbox)| {
+            let shift = SBOX_INPUT_BITS * (SBOXES_NUMBER - idx - 1) as u32;
+            let input = (mixed >> shift) & ((1 << SBOX_INPUT_BITS) - 1);
+            (merged << SBOX_OUTPUT_BITS) | sbox.apply(input)
+        });
+        self.permutation.apply(merged) as u32
+    }
+
+    /// The defined cipher as a Feistel network with a given number of
+    /// rounds (keys repeat every 16 rounds, as in DES)
+    /// @returns network, which borrows the tables of the definition
+    pub fn network(&self, rounds: usize)
+                   -> FeistelNetwork<'_, impl Fn(u32, Key) -> u32 + Copy + Send + Sync + '_,
+                                     DefinedKeySchedule<'_>> {
+        FeistelNetwork::new(move |data, key| self.feistel_function(data, key),
+                            DefinedKeySchedule { definition: self })
+            .rounds(rounds)
+            .permutations(&self.initial_permutation, &self.final_permutation)
+    }
+
+    /// S-boxes S1..S8 of the defined cipher
+    pub fn sboxes(&self) -> &[EncodingTable<'static>] {
+        &self.sboxes
+    }
+
+    /// Traces encryption of a ‘data’ block with a ‘key’ by the defined
+    /// cipher with a given number of ‘rounds’, the way
+    /// ‘trace::trace_encryption’ does for DES
+    /// @returns trace of the key schedule and of the block
+    pub fn trace_encryption(&self, data: u64, key: u64, rounds: usize)
+                            -> (KeyScheduleTrace, BlockTrace) {
+        let inner_keys = self.inner_keys(key, rounds);
+        let (c0, d0) = trace::split_inner_key(inner_keys[0]);
+        let round_traces: Vec<KeyRoundTrace> = inner_keys.iter().enumerate().skip(1)
+            .map(|(round, &inner_key)| {
+                let (c, d) = trace::split_inner_key(inner_key);
+                KeyRoundTrace {
+                    rotation: self.rotations[(round - 1) % ROTATIONS_NUMBER],
+                    c,
+                    d,
+                    subkey: self.round_key(inner_key),
+                }
+            }).collect();
+        let round_keys: Vec<Key> = round_traces.iter().map(|r| r.subkey).collect();
+        let tables = TracedTables {
+            initial_permutation: &self.initial_permutation,
+            final_permutation: &self.final_permutation,
+            expansion: &self.expansion,
+            permutation: &self.permutation,
+            granulations: &self.sboxes,
+        };
+        let block = trace::trace_block_with(&tables, data, &round_keys);
+        (KeyScheduleTrace { key, c0, d0, rounds: round_traces }, block)
+    }
+
+    /// Permutes the ‘key’ by PC-1 and rotates both 28 bit halves before
+    /// every of the ‘rounds’
+    /// @returns inner keys: the permuted one, then one per round
+    fn inner_keys(&self, key: u64, rounds: usize) -> Vec<Key> {
+        let split_bit = BITS_IN_INNER_KEY / 2;
+        let mut inner_key = self.key_permutation.apply(key);
+        let mut inner_keys = vec![Key { value: inner_key, size_bits: BITS_IN_INNER_KEY }];
+        for round in 0..rounds {
+            let shift = self.rotations[round % ROTATIONS_NUMBER];
+            inner_key = low::rotate_range_to_high(
+                inner_key, 0, split_bit, shift, BITS_IN_INNER_KEY).unwrap();
+            inner_key = low::rotate_range_to_high(
+                inner_key, split_bit, BITS_IN_INNER_KEY, shift, BITS_IN_INNER_KEY).unwrap();
+            inner_keys.push(Key { value: inner_key, size_bits: BITS_IN_INNER_KEY });
+        }
+        inner_keys
+    }
+
+    /// @returns round key, picked by PC-2 from the ‘inner_key’
+    fn round_key(&self, inner_key: Key) -> Key {
+        Key {
+            value: self.permuting_choice.apply(inner_key.value),
+            size_bits: BITS_IN_ROUND_KEY,
+        }
+    }
+}
+
+/// Key schedule of DES with the tables of a definition: the key is
+/// permuted by PC-1, then before every round both 28 bit halves are
+/// rotated, and PC-2 picks the round key
+#[derive(Copy, Clone)]
+pub struct DefinedKeySchedule<'a> {
+    definition: &'a CipherDefinition,
+}
+
+impl KeySchedule for DefinedKeySchedule<'_> {
+    fn encrypting_keys(&self, key: u64, rounds: usize) -> Vec<Key> {
+        let definition = self.definition;
+        definition.inner_keys(key, rounds).into_iter().skip(1)
+            .map(|inner_key| definition.round_key(inner_key))
+            .collect()
+    }
+}
+
+/// @returns Result with an error, if the ‘section’ doesn’t have
+/// ‘expected’ entries
+fn check_length(section: &'static str, entries: &[u64], expected: usize)
+                -> Result<(), ParseCipherDefError> {
+    if entries.len() != expected {
+        return Err(ParseCipherDefError::BadLength { section, count: entries.len(), expected });
+    }
+    Ok(())
+}
+
+/// Validates the ‘section’ of ‘output_size’ bit numbers of
+/// ‘input_size’ bit input (counted from 1), and builds its table. If
+/// the bits are ‘distinct’, none of them can be taken twice
+/// @returns Result with permutation table
+fn permutation_table(section: &'static str, entries: &[u64], input_size: u32,
+                     output_size: u32, distinct: bool)
+                     -> Result<PermutationTable, ParseCipherDefError> {
+    check_length(section, entries, output_size as usize)?;
+    let mut taken = 0u64;
+    for &bit in entries.iter() {
+        if bit < BIT_COUNT_FROM as u64 || bit - (BIT_COUNT_FROM as u64) >= input_size as u64 {
+            return Err(ParseCipherDefError::OutOfRange { section, value: bit });
+        }
+        let mask = 1 << (bit - BIT_COUNT_FROM as u64);
+        if distinct && taken & mask != 0 {
+            return Err(ParseCipherDefError::RepeatedBit { section, bit });
+        }
+        taken |= mask;
+    }
+    let destinations: Vec<u32> = entries.iter().map(|&bit| bit as u32).collect();
+    Ok(PermutationTable::new(&destinations, BIT_COUNT_FROM, input_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{des_network, DES_ROUNDS_NUMBER};
+
+    const DES_DEFINITION: &str = include_str!("../../../bin/des.def");
+
+    #[test]
+    fn test_des_definition() {
+        let definition = CipherDefinition::parse(DES_DEFINITION).expect("Valid DES definition");
+        let network = definition.network(DES_ROUNDS_NUMBER);
+        assert_eq!(network.encrypt_block(0x0123456789ABCDEF, 0x133457799BBCDFF1),
+                   0x85E813540F0AB405, "DES definition encryption");
+        assert_eq!(network.decrypt_block(0x85E813540F0AB405, 0x133457799BBCDFF1),
+                   0x0123456789ABCDEF, "DES definition decryption");
+        for &rounds in [4, 24].iter() {
+            assert_eq!(definition.network(rounds).encrypt_block(0x596F7572206C6970, 0x0E329232EA6D0D73),
+                       des_network(rounds).encrypt_block(0x596F7572206C6970, 0x0E329232EA6D0D73),
+                       "{} rounds of DES definition differ from DES", rounds);
+        }
+    }
+
+    #[test]
+    fn test_definition_trace() {
+        let definition = CipherDefinition::parse(DES_DEFINITION).expect("Valid DES definition");
+        for &rounds in [16, 20].iter() {
+            let (schedule, block) = definition.trace_encryption(0x0123456789ABCDEF, 0x133457799BBCDFF1, rounds);
+            let (des_schedule, des_block) = trace::trace_encryption(0x0123456789ABCDEF, 0x133457799BBCDFF1, rounds);
+            assert_eq!((schedule.c0, schedule.d0), (des_schedule.c0, des_schedule.d0), "C0 D0");
+            for (round, des_round) in schedule.rounds.iter().zip(des_schedule.rounds.iter()) {
+                assert_eq!((round.rotation, round.c, round.d, round.subkey.value),
+                           (des_round.rotation, des_round.c, des_round.d, des_round.subkey.value),
+                           "Key schedule of {} rounds differs from DES", rounds);
+            }
+            assert_eq!(block.preoutput, des_block.preoutput, "{} rounds preoutput", rounds);
+            assert_eq!(block.output, des_block.output, "{} rounds output", rounds);
+        }
+    }
+
+    #[test]
+    fn test_modified_definition() {
+        // S1 with its rows swapped is still balanced
+        let text = DES_DEFINITION.replacen(
+            "14  4 13  1  2 15 11  8  3 10  6 12  5  9  0  7\n 0 15  7  4 14  2 13  1 10  6 12 11  9  5  3  8",
+            " 0 15  7  4 14  2 13  1 10  6 12 11  9  5  3  8\n14  4 13  1  2 15 11  8  3 10  6 12  5  9  0  7", 1);
+        assert_ne!(text, DES_DEFINITION, "S1 is found in the definition");
+        let definition = CipherDefinition::parse(&text).expect("Valid modified definition");
+        let network = definition.network(DES_ROUNDS_NUMBER);
+        let encrypted = network.encrypt_block(0x0123456789ABCDEF, 0x133457799BBCDFF1);
+        assert_ne!(encrypted, 0x85E813540F0AB405, "Modified S-box should change the cipher");
+        assert_eq!(network.decrypt_block(encrypted, 0x133457799BBCDFF1), 0x0123456789ABCDEF,
+                   "Modified cipher should decrypt its own encryption");
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let replace = |from: &str, to: &str| DES_DEFINITION.replacen(from, to, 1);
+        assert_eq!(CipherDefinition::parse(&replace("[ip]\n58 50", "[ip]\n50 50")).err(),
+                   Some(ParseCipherDefError::RepeatedBit { section: "ip", bit: 50 }),
+                   "IP should be a bijection");
+        assert_eq!(CipherDefinition::parse(&replace("[fp]\n40  8 48", "[fp]\n8 40 48")).err(),
+                   Some(ParseCipherDefError::NotInverse), "FP should restore the block");
+        assert_eq!(CipherDefinition::parse(&replace("# S1\n14  4", "# S1\n 4  4")).err(),
+                   Some(ParseCipherDefError::UnbalancedSBox { sbox: 0, row: 0 }),
+                   "S-box rows should be balanced");
+        assert_eq!(CipherDefinition::parse(&replace("[expansion]\n32", "[expansion]\n33")).err(),
+                   Some(ParseCipherDefError::OutOfRange { section: "expansion", value: 33 }),
+                   "Expansion refers to a bit out of a half");
+        assert_eq!(CipherDefinition::parse(&replace("1 1 2 2 2", "1 1 2 2")).err(),
+                   Some(ParseCipherDefError::BadLength { section: "rotations", count: 15, expected: 16 }),
+                   "Rotation for every round is needed");
+        assert_eq!(CipherDefinition::parse(&replace("[pc2]", "[pc3]")).err(),
+                   Some(ParseCipherDefError::UnknownSection("pc3".to_string())));
+        assert_eq!(CipherDefinition::parse(&replace("[p]", "[ip]")).err(),
+                   Some(ParseCipherDefError::RepeatedSection("ip")));
+        assert_eq!(CipherDefinition::parse("[ip]\n1").err(),
+                   Some(ParseCipherDefError::MissingSection("fp")));
+        assert_eq!(CipherDefinition::parse(&format!("58\n{}", DES_DEFINITION)).err(),
+                   Some(ParseCipherDefError::OutsideSection("58".to_string())),
+                   "Numbers before the first section belong nowhere");
+        assert_eq!(CipherDefinition::parse(&replace("[ip]\n58", "[ip]\n5x")).err(),
+                   Some(ParseCipherDefError::NotANumber("5x".to_string())));
+    }
+}
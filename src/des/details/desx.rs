@@ -0,0 +1,69 @@
+//! DES-X (Rivest): DES with key whitening. The block is XORed with a
+//! pre-whitening key before DES and with a post-whitening key after it,
+//! so the key is 56 + 64 + 64 = 184 bits. It costs only two XORs per
+//! block, but makes exhaustive search of the DES key alone useless
+
+use super::algorithm::{encrypt_block_separately, decrypt_block_separately};
+
+/// Key of DES-X, the parts are in the order of RSA’s BSAFE (and
+/// OpenSSL): DES key, pre-whitening key, post-whitening key
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DesXKey {
+    pub key: u64,
+    pub pre_whitening: u64,
+    pub post_whitening: u64,
+}
+
+impl DesXKey {
+    pub fn new(key: u64, pre_whitening: u64, post_whitening: u64) -> Self {
+        Self { key, pre_whitening, post_whitening }
+    }
+}
+
+/// Takes a piece of data of size 64 bit, encrypts it with DES-X
+/// (making whole cycle of scheduling aswell)
+/// @returns encrypted piece of data
+pub fn desx_encrypt_block(data: u64, key: DesXKey) -> u64 {
+    encrypt_block_separately(data ^ key.pre_whitening, key.key) ^ key.post_whitening
+}
+
+/// Takes a piece of data of size 64 bit, decrypts it with DES-X
+/// (making whole cycle of scheduling aswell)
+/// @returns decrypted piece of data
+pub fn desx_decrypt_block(data: u64, key: DesXKey) -> u64 {
+    decrypt_block_separately(data ^ key.post_whitening, key.key) ^ key.pre_whitening
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DESX-CBC vector of OpenSSL’s test suite (evpciph), the chaining
+    /// is done by hand
+    #[test]
+    fn test_desx_vector() {
+        let key = DesXKey::new(0x0123456789ABCDEF, 0xF1E0D3C2B5A49786, 0xFEDCBA9876543210);
+        let iv = 0xFEDCBA9876543210;
+        let plaintext = [0x3736353433323120, 0x4E6F772069732074,
+                         0x68652074696D6520, 0x666F722000000000];
+        let ciphertext = [0x846B2914851E9A29, 0x54732F8AA0A611C1,
+                          0x15CDC2D7951B1053, 0xA63C5E03B21AA3C4];
+        let mut chain = iv;
+        for (&p, &c) in plaintext.iter().zip(ciphertext.iter()) {
+            chain = desx_encrypt_block(p ^ chain, key);
+            assert_eq!(chain, c, "DES-X encryption of {:#018X}", p);
+        }
+        let mut chain = iv;
+        for (&p, &c) in plaintext.iter().zip(ciphertext.iter()) {
+            assert_eq!(desx_decrypt_block(c, key) ^ chain, p, "DES-X decryption of {:#018X}", c);
+            chain = c;
+        }
+    }
+
+    #[test]
+    fn test_zero_whitening_is_des() {
+        let key = DesXKey::new(0x133457799BBCDFF1, 0, 0);
+        assert_eq!(desx_encrypt_block(0x0123456789ABCDEF, key), 0x85E813540F0AB405,
+                   "DES-X without whitening should be DES");
+    }
+}
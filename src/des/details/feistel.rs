@@ -0,0 +1,175 @@
+//! Generic Feistel network over 64 bit blocks. Block is split into two
+//! 32 bit halves, on each round the low half goes through the round
+//! function (with a round key) and is mixed into the high half, then
+//! the halves are swapped (besides the last round).
+//!
+//! DES is one configuration of this network (16 rounds, DES key
+//! schedule, initial and reverse permutations), but any number of
+//! rounds, round function, permutations and key schedule can be used
+
+use super::{Key, KeyScheduler, PermutationTable};
+
+const BITS_IN_HALF: u32 = 32;
+pub const DES_ROUNDS_NUMBER: usize = 16;
+
+/// Generator of round keys from a user’s key
+pub trait KeySchedule {
+    /// @returns round keys in order of encryption, one per round
+    fn encrypting_keys(&self, key: u64, rounds: usize) -> Vec<Key>;
+
+    /// @returns round keys in order of decryption, one per round
+    fn decrypting_keys(&self, key: u64, rounds: usize) -> Vec<Key> {
+        let mut keys = self.encrypting_keys(key, rounds);
+        keys.reverse();
+        keys
+    }
+}
+
+/// Standard DES key schedule. If more than 16 rounds are requested,
+/// the keys repeat from the first one (inner key makes a full turn
+/// every 16 rounds)
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DesKeySchedule;
+
+impl KeySchedule for DesKeySchedule {
+    fn encrypting_keys(&self, key: u64, rounds: usize) -> Vec<Key> {
+        KeyScheduler::new_encrypting(key).take(rounds).collect()
+    }
+}
+
+/// Feistel network with a pluggable round function and key schedule,
+/// permutations of the block are borrowed for lifetime ‘'p’
+pub struct FeistelNetwork<'p, F, S> {
+    rounds: usize,
+    initial_permutation: Option<&'p PermutationTable>,
+    final_permutation: Option<&'p PermutationTable>,
+    round_function: F,
+    key_schedule: S,
+}
+
+impl<'p, F, S> FeistelNetwork<'p, F, S>
+where F: Fn(u32, Key) -> u32, S: KeySchedule {
+    /// Builds a 16 rounds network with no permutations of the block
+    /// @returns constructed network
+    pub fn new(round_function: F, key_schedule: S) -> Self {
+        Self {
+            rounds: DES_ROUNDS_NUMBER,
+            initial_permutation: None,
+            final_permutation: None,
+            round_function,
+            key_schedule,
+        }
+    }
+
+    /// Set number of rounds
+    /// @returns modified network (builder pattern)
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Set permutations of the block before the first round and after
+    /// the last round
+    /// @returns modified network (builder pattern)
+    pub fn permutations(mut self, initial: &'p PermutationTable,
+                        last: &'p PermutationTable) -> Self {
+        self.initial_permutation = Some(initial);
+        self.final_permutation = Some(last);
+        self
+    }
+
+    /// Generates round keys for encryption with this network
+    pub fn encrypting_keys(&self, key: u64) -> Vec<Key> {
+        self.key_schedule.encrypting_keys(key, self.rounds)
+    }
+
+    /// Generates round keys for decryption with this network
+    pub fn decrypting_keys(&self, key: u64) -> Vec<Key> {
+        self.key_schedule.decrypting_keys(key, self.rounds)
+    }
+
+    /// Takes a piece of data of size 64 bit, encrypts it using a given
+    /// key (making whole cycle of scheduling aswell)
+    /// @returns encrypted piece of data
+    pub fn encrypt_block(&self, data: u64, key: u64) -> u64 {
+        self.act_on_block(data, &mut self.encrypting_keys(key).into_iter())
+    }
+
+    /// Takes a piece of data of size 64 bit, decrypts it using a given
+    /// key (making whole cycle of scheduling aswell)
+    /// @returns decrypted piece of data
+    pub fn decrypt_block(&self, data: u64, key: u64) -> u64 {
+        self.act_on_block(data, &mut self.decrypting_keys(key).into_iter())
+    }
+
+    /// Runs the network over a block, taking a round key per round
+    /// from the ‘key_iterator’. Halves aren’t swapped after the last
+    /// round, so the same network decrypts with reversed keys
+    /// @returns encrypted / decrypted piece of data, depending on keys given
+    #[inline]
+    pub fn act_on_block<I>(&self, mut data: u64, key_iterator: &mut I) -> u64
+    where I: Iterator<Item=Key> {
+        if let Some(permutation) = self.initial_permutation {
+            data = permutation.apply(data);
+        }
+        let (mut high_half, mut low_half) = ((data >> BITS_IN_HALF) as u32, data as u32);
+        for (_, round_key) in (0..self.rounds).zip(key_iterator.by_ref()) {
+            let new_low = high_half ^ (self.round_function)(low_half, round_key);
+            high_half = low_half;
+            low_half = new_low;
+        }
+        data = ((low_half as u64) << BITS_IN_HALF) | high_half as u64;
+        if let Some(permutation) = self.final_permutation {
+            data = permutation.apply(data);
+        }
+        data
+    }
+}
+
+/// Field access functions
+impl<F, S> FeistelNetwork<'_, F, S> {
+    pub fn rounds_number(&self) -> usize { self.rounds }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{des_network, encrypt_block_separately};
+
+    #[test]
+    fn test_des_configuration() {
+        let des = des_network(DES_ROUNDS_NUMBER);
+        assert_eq!(des.encrypt_block(0x0123456789ABCDEF, 0x133457799BBCDFF1),
+                   0x85E813540F0AB405, "DES configured network encryption");
+        assert_eq!(des.decrypt_block(0x85E813540F0AB405, 0x133457799BBCDFF1),
+                   0x0123456789ABCDEF, "DES configured network decryption");
+    }
+
+    #[test]
+    fn test_reduced_and_extended_rounds() {
+        let key = 0x0E329232EA6D0D73;
+        let data = 0x596F7572206C6970;
+        for &rounds in [1, 4, 6, 8, 16, 24, 32].iter() {
+            let des = des_network(rounds);
+            let encrypted = des.encrypt_block(data, key);
+            assert_eq!(des.decrypt_block(encrypted, key), data,
+                       "{} rounds network doesn't decrypt its own encryption", rounds);
+            if rounds != DES_ROUNDS_NUMBER {
+                assert_ne!(encrypted, encrypt_block_separately(data, key),
+                           "{} rounds network is the same as DES", rounds);
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_round_function() {
+        // the last round isn’t swapped, so a single round with zero
+        // round function keeps the data
+        let network = FeistelNetwork::new(|_, _| 0, DesKeySchedule).rounds(1);
+        assert_eq!(network.encrypt_block(0x0123456789ABCDEF, 0), 0x0123456789ABCDEF,
+                   "Single round with zero function should keep the block");
+        let network = FeistelNetwork::new(|half, _| half, DesKeySchedule).rounds(1);
+        assert_eq!(network.encrypt_block(0x0000000100000001, 0), 0x0000000000000001,
+                   "Single round should XOR the round function into the high half");
+    }
+}
@@ -1,30 +1,25 @@
 use crate::math::bit_arithmetics;
 use super::{PermutationTable};
 
-use crate::lazy_static::*;
 const BITS_IN_INPUT: u32 = 64;
 const BITS_ORDER_L_TO_R: bool = true;
 const BIT_COUNT_FROM: u32 = 1;
 const ROTATIONS_TO_WRAPPING: usize = 16;
 
 /// Initial key permutation table. Is applied before any key is generated
-lazy_static! {
-    static ref INITIAL_PERMUTATION: PermutationTable = PermutationTable::new(vec![
-        57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18,
-        10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60, 52, 44, 36,
-        63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22,
-        14, 6, 61, 53, 45, 37, 29, 21, 13, 5, 28, 20, 12, 4
-    ], BIT_COUNT_FROM, BITS_IN_INPUT);
-}
+static INITIAL_PERMUTATION: PermutationTable = PermutationTable::new(&[
+    57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18,
+    10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60, 52, 44, 36,
+    63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22,
+    14, 6, 61, 53, 45, 37, 29, 21, 13, 5, 28, 20, 12, 4
+], BIT_COUNT_FROM, BITS_IN_INPUT);
 
 /// Maps 56-bit long key to a 48-bit long round key
-lazy_static! {
-    static ref PERMUTING_CHOICE: PermutationTable = PermutationTable::new(vec![
-        14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, 23, 19, 12, 4,
-        26, 8, 16, 7, 27, 20, 13, 2, 41, 52, 31, 37, 47, 55, 30, 40,
-        51, 45, 33, 48, 44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
-    ], BIT_COUNT_FROM, 56);    
-}
+static PERMUTING_CHOICE: PermutationTable = PermutationTable::new(&[
+    14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, 23, 19, 12, 4,
+    26, 8, 16, 7, 27, 20, 13, 2, 41, 52, 31, 37, 47, 55, 30, 40,
+    51, 45, 33, 48, 44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+], BIT_COUNT_FROM, 56);
 
 /// Encryption / Decryption key -> a binary value of some length
 #[derive(Debug, Clone, Copy, Default)]
@@ -34,7 +29,7 @@ pub struct Key {
 }
 
 /// Scheduler that generates a new key from the previous key
-//#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone)]
 pub struct KeyScheduler {
     des_key_size_bits: u32,
     current_key_index: usize,
@@ -72,16 +67,11 @@ impl KeyScheduler {
         let mut scheduler = EncryptingKeyScheduler {
             base: KeyScheduler::new(initial_key, ROTATIONS_TO_WRAPPING),
         };
-        println!("{:?}", scheduler.base.cache[0]);
-        println!("{:?}", scheduler.base.initial_key);
-        scheduler.base.cache[0] = KeyScheduler::des_key(scheduler.base.initial_key);
-        println!("{:?}", scheduler.base.cache[0]);
         let mut next_inner_key = scheduler.base.initial_key;
-        for i in 1..ROTATIONS_TO_WRAPPING {
+        for i in 0..ROTATIONS_TO_WRAPPING {
             next_inner_key = scheduler.base.rotate_key(
-                next_inner_key, false).unwrap();
+                next_inner_key, i + 1, true).unwrap();
             scheduler.base.cache[i] = KeyScheduler::des_key(next_inner_key);
-            println!("{:?}", scheduler.base.cache[i]);
         }
         scheduler
     }
@@ -91,26 +81,26 @@ impl KeyScheduler {
         let mut scheduler = DecryptingKeyScheduler {
             base: KeyScheduler::new(initial_key, ROTATIONS_TO_WRAPPING),
         };
+        // After all the rotations inner key wraps to its initial state,
+        // so the last round key is derived right from it
+        let mut next_inner_key = scheduler.base.initial_key;
+        scheduler.base.cache[0] = KeyScheduler::des_key(next_inner_key);
         for i in 1..ROTATIONS_TO_WRAPPING {
-            scheduler.base.cache[i] = scheduler.base.rotate_key(
-                scheduler.base.cache[i-1], true).unwrap();
+            next_inner_key = scheduler.base.rotate_key(
+                next_inner_key, ROTATIONS_TO_WRAPPING - i + 1, false).unwrap();
+            scheduler.base.cache[i] = KeyScheduler::des_key(next_inner_key);
         }
         scheduler
     }
 
     /// Rotate the inner key, according to DES scheduling specification
-    /// (some rotations are by 1, some by 2), in direction, determined
-    /// by ‘to_high’ boolean
+    /// (some rotations are by 1, some by 2, depending on the ‘round’
+    /// counted from 1), in direction, determined by ‘to_high’ boolean
     /// @returns Option with a new inner key, where two ranges are
     /// separately rotated towards high/low bits
-    fn rotate_key(&self, mut key: Key, to_high: bool) -> Option<Key>{
+    fn rotate_key(&self, mut key: Key, round: usize, to_high: bool) -> Option<Key>{
         use bit_arithmetics::idx_from_low as low;
-        let shift_for = match self.current_key_index {
-            1 | 2 | 9 | 16 => 1,
-            0 => 0, // FIXME when generating decrypting keys, no
-            // rotation is needed, but it doesn’t seem good solution
-            _ => 2,
-        };
+        let shift_for = KeyScheduler::rotation_for_round(round);
         
         let rotate = if to_high {
             low::rotate_range_to_high
@@ -132,20 +122,45 @@ impl KeyScheduler {
         Some(key)
     }
 
+    /// Number of bits, for which inner key halves are rotated before
+    /// a given ‘round’ (counted from 1, repeats every 16 rounds)
+    pub fn rotation_for_round(round: usize) -> u32 {
+        match (round - 1) % ROTATIONS_TO_WRAPPING + 1 {
+            1 | 2 | 9 | 16 => 1,
+            _ => 2,
+        }
+    }
+
+    /// Generates inner keys (56 bit wide, two 28 bit halves C and D),
+    /// starting with the permuted initial key, and then the inner key
+    /// after rotation before each of ‘rounds’ rounds of encryption
+    /// @returns ‘rounds’ + 1 inner keys
+    pub fn inner_keys(initial_key: u64, rounds: usize) -> Vec<Key> {
+        let scheduler = KeyScheduler::new(initial_key, 0);
+        let mut inner_keys = vec![scheduler.initial_key];
+        for round in 1..=rounds {
+            let rotated = scheduler.rotate_key(inner_keys[round - 1], round, true).unwrap();
+            inner_keys.push(rotated);
+        }
+        inner_keys
+    }
+
     /// Extract DES round key from inner scheduled 
     pub fn des_key(key: Key) -> Key {
         let value = PERMUTING_CHOICE.apply(key.value);
-        Key { value, ..key }
+        Key { value, size_bits: PERMUTING_CHOICE.output_size() }
     }
 }
 
 /// Generates another key by left rotation of previous (as specified
 /// in DES specification)
+#[derive(Clone)]
 pub struct EncryptingKeyScheduler {
     base: KeyScheduler,
 }
 /// Generates another key by right rotation of previous (as specified
 /// in DES specification)
+#[derive(Clone)]
 pub struct DecryptingKeyScheduler {
     base: KeyScheduler,
 }
@@ -182,7 +197,7 @@ impl Iterator for DecryptingKeyScheduler {
             self.base.current_key_index -= 1;
         }
         let cache_idx = (ROTATIONS_TO_WRAPPING - self.base.current_key_index) % ROTATIONS_TO_WRAPPING;
-        Some(KeyScheduler::des_key(self.base.cache[cache_idx]))
+        Some(self.base.cache[cache_idx])
     }
 }
 #[cfg(test)]
@@ -305,8 +320,6 @@ mod tests {
                    0b_000110_110000_001011_101111_111111_000111_000001_110010, "k1");
         assert_eq!(s.next().unwrap().value,
                    0b_110010_110011_110110_001011_000011_100001_011111_110101, "k16_cache");
-        assert_eq!(s.next().unwrap().value,
-                   0b_110010_110011_110110_001011_000011_100001_011111_110101, "k16_cache");
         assert_eq!(s.next().unwrap().value,
                    0b_101111_111001_000110_001101_001111_010011_111100_001010, "k15_cache");
         assert_eq!(s.next().unwrap().value,
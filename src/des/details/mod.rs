@@ -13,5 +13,19 @@ pub use self::bit_permutation::*;
 pub mod round;
 pub use self::round::*;
 
+pub mod table_round;
+pub use self::table_round::*;
+
+pub mod feistel;
+pub use self::feistel::*;
+
 pub mod algorithm;
 pub use self::algorithm::*;
+
+pub mod desx;
+pub use self::desx::*;
+
+pub mod definition;
+pub use self::definition::*;
+
+pub mod trace;
@@ -0,0 +1,122 @@
+//! Table-optimized DES round. Gives the same results as the functions
+//! from ‘round’ and ‘algorithm’ modules, but instead of expanding,
+//! granulating and permuting data piece by piece, it looks up 8 SP
+//! tables, each merges a granulation (S-box) with the final
+//! permutation (P) of the round, so there is one lookup per 6 bit chunk
+
+use super::algorithm::des_network;
+use super::feistel::DES_ROUNDS_NUMBER;
+use super::round::{GRANULATIONS, FINAL_PERMUTATION};
+use super::Key;
+
+const BITS_IN_HALF: u32 = 32;
+const GRANULATIONS_NUMBER: usize = 8;
+const GRANULATION_INPUT_VALUES: usize = 1 << 6;
+const GRANULATION_INPUT_SIZE_BITS: u32 = 6;
+const GRANULATION_OUTPUT_SIZE_BITS: u32 = 4;
+const GRANULATION_INPUT_MASK: u32 = 0b11_1111;
+
+type SpTables = [[u32; GRANULATION_INPUT_VALUES]; GRANULATIONS_NUMBER];
+
+/// Granulations, merged with the final permutation of the round. A
+/// chunk of 6 bits (already XORed with the round key) is looked up in
+/// the table of its granulation, the result is a 32 bit number with
+/// the 4 encoded bits already in their permuted places
+static SP_TABLES: SpTables = sp_tables();
+
+/// Merges every granulation with the final permutation of the round
+/// @returns SP tables, indexed first by granulation, then by its input
+const fn sp_tables() -> SpTables {
+    let mut tables = [[0; GRANULATION_INPUT_VALUES]; GRANULATIONS_NUMBER];
+    let mut g = 0;
+    while g < GRANULATIONS_NUMBER {
+        let shift = GRANULATION_OUTPUT_SIZE_BITS
+            * (GRANULATIONS_NUMBER - g - 1) as u32;
+        let mut chunk = 0;
+        while chunk < GRANULATION_INPUT_VALUES {
+            let encoded = GRANULATIONS[g].apply(chunk as u64);
+            tables[g][chunk] = FINAL_PERMUTATION.apply(encoded << shift) as u32;
+            chunk += 1;
+        }
+        g += 1;
+    }
+    tables
+}
+
+/// Obscuring function, operating on 32 bit piece of data with a
+/// 48 bit round key. The expansion is done by taking overlapping 6
+/// bit windows of rotated data, which then go through SP tables
+/// @returns obscured with the key piece of data
+#[inline]
+pub fn table_feilstel_function(data: u32, key: Key) -> u32 {
+    let mut merged_data = 0;
+    for (g, table) in SP_TABLES.iter().enumerate() {
+        // g-th window starts 1 bit before the g-th nibble of data
+        // (wrapping around), and ends 1 bit after it
+        let window_end = (2 * BITS_IN_HALF - 5 - 4 * g as u32) % BITS_IN_HALF;
+        let expanded = data.rotate_right(window_end) & GRANULATION_INPUT_MASK;
+        let key_shift = GRANULATION_INPUT_SIZE_BITS
+            * (GRANULATIONS_NUMBER - g - 1) as u32;
+        let key_chunk = (key.value >> key_shift) as u32 & GRANULATION_INPUT_MASK;
+        merged_data |= table[(expanded ^ key_chunk) as usize];
+    }
+    merged_data
+}
+
+/// A single table-optimized DES encryption round
+/// @returns the same as ‘encrypt_round’
+#[inline]
+pub fn table_encrypt_round(data: u64, key: Key) -> u64 {
+    let (high_half, low_half) = ((data >> BITS_IN_HALF) as u32, data as u32);
+    let new_low = high_half ^ table_feilstel_function(low_half, key);
+    ((low_half as u64) << BITS_IN_HALF) | new_low as u64
+}
+
+/// Table-optimized DES over a block
+/// @returns encrypted / decrypted piece of data, depending on keys given
+#[inline]
+pub fn table_act_on_block<I>(data: u64, key_iterator: &mut I) -> u64
+where I: Iterator<Item=Key> {
+    des_network(DES_ROUNDS_NUMBER).act_on_block(data, key_iterator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{encrypt_round, KeyScheduler};
+
+    #[test]
+    fn test_table_feilstel_function() {
+        assert_eq!(
+            table_feilstel_function(
+                0b1111_0000_1010_1010_1111_0000_1010_1010,
+                Key {value:0b000110_110000_001011_101111_111111_000111_000001_110010, size_bits:48}),
+            0b0010_0011_0100_1010_1010_1001_1011_1011,
+            "Table-optimized Feilsel function doesn't work properly"
+        );
+    }
+
+    #[test]
+    fn test_table_encrypt_round() {
+        let mut value = 0xFEDCBA9876543210_u64;
+        for key in KeyScheduler::new_encrypting(0x133457799BBCDFF1).take(64) {
+            assert_eq!(table_encrypt_round(value, key), encrypt_round(value, key),
+                       "Table-optimized round differs from the reference one");
+            value = value.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        }
+    }
+
+    #[test]
+    fn test_table_act_on_block() {
+        assert_eq!(
+            table_act_on_block(0x0123456789ABCDEF, &mut KeyScheduler::new_encrypting(0x133457799BBCDFF1)),
+            0x85E813540F0AB405,
+            "Table-optimized encryption"
+        );
+        assert_eq!(
+            table_act_on_block(0x85E813540F0AB405, &mut KeyScheduler::new_decrypting(0x133457799BBCDFF1)),
+            0x0123456789ABCDEF,
+            "Table-optimized decryption"
+        );
+    }
+}
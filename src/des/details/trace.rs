@@ -0,0 +1,253 @@
+//! Round by round trace of DES, for teaching and debugging. Every
+//! intermediate value of the key schedule and of block’s encryption /
+//! decryption is recorded. Tracing goes its own way through the
+//! reference tables, so the fast path doesn’t pay anything for it
+
+use super::algorithm::{INITIAL_PERMUTATION, REVERSE_PERMUTATION};
+use super::round::{EXPANSION, GRANULATIONS, FINAL_PERMUTATION};
+use super::{EncodingTable, Key, KeyScheduler, PermutationTable};
+
+const BITS_IN_HALF: u32 = 32;
+const INNER_KEY_HALF_BITS: u32 = 28;
+const GRANULATIONS_NUMBER: usize = 8;
+const GRANULATION_INPUT_SIZE_BITS: u32 = 6;
+const GRANULATION_OUTPUT_SIZE_BITS: u32 = 4;
+
+/// Tables, through which a block is traced: those of DES, or of a
+/// DES-like cipher with the same structure
+#[derive(Copy, Clone)]
+pub struct TracedTables<'a> {
+    pub initial_permutation: &'a PermutationTable,
+    pub final_permutation: &'a PermutationTable,
+    pub expansion: &'a PermutationTable,
+    /// Permutation P of the granulations output
+    pub permutation: &'a PermutationTable,
+    /// Granulations S1..S8
+    pub granulations: &'a [EncodingTable<'static>],
+}
+
+impl TracedTables<'static> {
+    /// @returns tables of the standard DES
+    pub fn des() -> Self {
+        Self {
+            initial_permutation: &INITIAL_PERMUTATION,
+            final_permutation: &REVERSE_PERMUTATION,
+            expansion: &EXPANSION,
+            permutation: &FINAL_PERMUTATION,
+            granulations: &GRANULATIONS,
+        }
+    }
+}
+
+/// State of the key schedule before a single round
+#[derive(Clone, Debug)]
+pub struct KeyRoundTrace {
+    /// Bits, for which halves were rotated before this round
+    pub rotation: u32,
+    /// Upper 28 bit half of the inner key, after rotation
+    pub c: u32,
+    /// Lower 28 bit half of the inner key, after rotation
+    pub d: u32,
+    /// 48 bit round key (permuted choice of the inner key)
+    pub subkey: Key,
+}
+
+/// Whole key schedule
+#[derive(Clone, Debug)]
+pub struct KeyScheduleTrace {
+    /// Key, as it was given by a user (64 bit, with parity bits)
+    pub key: u64,
+    /// Upper half of the permuted key, before any rotation
+    pub c0: u32,
+    /// Lower half of the permuted key, before any rotation
+    pub d0: u32,
+    /// Rotations and round keys, in order of encryption
+    pub rounds: Vec<KeyRoundTrace>,
+}
+
+/// A single round of DES
+#[derive(Clone, Debug)]
+pub struct RoundTrace {
+    /// Left half, given to the round
+    pub left: u32,
+    /// Right half, given to the round
+    pub right: u32,
+    /// Round key, used on this round
+    pub subkey: Key,
+    /// Right half, expanded to 48 bits
+    pub expansion: u64,
+    /// 6 bit inputs of granulations S1..S8 (expansion XOR round key)
+    pub sbox_inputs: [u8; GRANULATIONS_NUMBER],
+    /// 4 bit outputs of granulations S1..S8
+    pub sbox_outputs: [u8; GRANULATIONS_NUMBER],
+    /// Granulations output after the round’s permutation P (i.e.
+    /// result of the Feistel function)
+    pub permutation: u32,
+    /// Left half after the round (it’s the right half given)
+    pub new_left: u32,
+    /// Right half after the round (left half XOR Feistel function)
+    pub new_right: u32,
+}
+
+/// Whole encryption / decryption of a block
+#[derive(Clone, Debug)]
+pub struct BlockTrace {
+    pub input: u64,
+    /// Block after the initial permutation
+    pub initial_permutation: u64,
+    pub rounds: Vec<RoundTrace>,
+    /// Halves after the last round, swapped (R16 L16), before the
+    /// reverse permutation
+    pub preoutput: u64,
+    pub output: u64,
+}
+
+/// Traces key schedule of the ‘key’ for a given number of rounds
+/// @returns trace of the key schedule
+pub fn trace_key_schedule(key: u64, rounds: usize) -> KeyScheduleTrace {
+    let inner_keys = KeyScheduler::inner_keys(key, rounds);
+    let (c0, d0) = split_inner_key(inner_keys[0]);
+    let rounds = inner_keys.iter().enumerate().skip(1).map(|(round, &inner_key)| {
+        let (c, d) = split_inner_key(inner_key);
+        KeyRoundTrace {
+            rotation: KeyScheduler::rotation_for_round(round),
+            c,
+            d,
+            subkey: KeyScheduler::des_key(inner_key),
+        }
+    }).collect();
+    KeyScheduleTrace { key, c0, d0, rounds }
+}
+
+/// Traces DES encryption of a ‘data’ block with a ‘key’, which is
+/// reduced or extended to a given number of ‘rounds’ (16 for standard DES)
+/// @returns trace of the key schedule and of the block
+pub fn trace_encryption(data: u64, key: u64, rounds: usize) -> (KeyScheduleTrace, BlockTrace) {
+    let schedule = trace_key_schedule(key, rounds);
+    let round_keys: Vec<Key> = schedule.rounds.iter().map(|r| r.subkey).collect();
+    let block = trace_block(data, &round_keys);
+    (schedule, block)
+}
+
+/// Traces DES decryption of a ‘data’ block with a ‘key’, which is
+/// reduced or extended to a given number of ‘rounds’ (16 for standard
+/// DES). Round keys are used in reverse order
+/// @returns trace of the key schedule and of the block
+pub fn trace_decryption(data: u64, key: u64, rounds: usize) -> (KeyScheduleTrace, BlockTrace) {
+    let schedule = trace_key_schedule(key, rounds);
+    let round_keys: Vec<Key> = schedule.rounds.iter().rev().map(|r| r.subkey).collect();
+    let block = trace_block(data, &round_keys);
+    (schedule, block)
+}
+
+/// Traces DES over a block, with a round per each of ‘round_keys’
+/// @returns trace of every step
+pub fn trace_block(data: u64, round_keys: &[Key]) -> BlockTrace {
+    trace_block_with(&TracedTables::des(), data, round_keys)
+}
+
+/// Traces a DES-like cipher with the given ‘tables’ over a block, with
+/// a round per each of ‘round_keys’
+/// @returns trace of every step
+pub fn trace_block_with(tables: &TracedTables, data: u64, round_keys: &[Key]) -> BlockTrace {
+    let initial_permutation = tables.initial_permutation.apply(data);
+    let mut left = (initial_permutation >> BITS_IN_HALF) as u32;
+    let mut right = initial_permutation as u32;
+    let rounds: Vec<RoundTrace> = round_keys.iter().map(|&subkey| {
+        let round = trace_round(tables, left, right, subkey);
+        left = round.new_left;
+        right = round.new_right;
+        round
+    }).collect();
+    let preoutput = ((right as u64) << BITS_IN_HALF) | left as u64;
+    BlockTrace {
+        input: data,
+        initial_permutation,
+        rounds,
+        preoutput,
+        output: tables.final_permutation.apply(preoutput),
+    }
+}
+
+/// Traces a single round, given its input halves and a round key
+/// @returns trace of the round
+fn trace_round(tables: &TracedTables, left: u32, right: u32, subkey: Key) -> RoundTrace {
+    let expansion = tables.expansion.apply(right as u64);
+    let mixed = expansion ^ subkey.value;
+    let mut sbox_inputs = [0; GRANULATIONS_NUMBER];
+    let mut sbox_outputs = [0; GRANULATIONS_NUMBER];
+    let mut merged = 0;
+    for (g, granulation) in tables.granulations.iter().enumerate() {
+        let shift = GRANULATION_INPUT_SIZE_BITS * (GRANULATIONS_NUMBER - g - 1) as u32;
+        let input = (mixed >> shift) & ((1 << GRANULATION_INPUT_SIZE_BITS) - 1);
+        let output = granulation.apply(input);
+        sbox_inputs[g] = input as u8;
+        sbox_outputs[g] = output as u8;
+        merged = (merged << GRANULATION_OUTPUT_SIZE_BITS) | output;
+    }
+    let permutation = tables.permutation.apply(merged) as u32;
+    RoundTrace {
+        left,
+        right,
+        subkey,
+        expansion,
+        sbox_inputs,
+        sbox_outputs,
+        permutation,
+        new_left: right,
+        new_right: left ^ permutation,
+    }
+}
+
+/// @returns C and D halves of the inner key
+pub(crate) fn split_inner_key(inner_key: Key) -> (u32, u32) {
+    let mask = (1 << INNER_KEY_HALF_BITS) - 1;
+    ((inner_key.value >> INNER_KEY_HALF_BITS) as u32 & mask as u32,
+     inner_key.value as u32 & mask as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_key_schedule() {
+        let schedule = trace_key_schedule(0x133457799BBCDFF1, 16);
+        assert_eq!((schedule.c0, schedule.d0), (0xF0CCAAF, 0x556678F), "C0 D0");
+        assert_eq!((schedule.rounds[0].c, schedule.rounds[0].d), (0xE19955F, 0xAACCF1E), "C1 D1");
+        assert_eq!(schedule.rounds[0].subkey.value,
+                   0b_000110_110000_001011_101111_111111_000111_000001_110010, "K1");
+        assert_eq!((schedule.rounds[15].c, schedule.rounds[15].d),
+                   (schedule.c0, schedule.d0), "C16 D16 wrap to C0 D0");
+        let rotations: Vec<u32> = schedule.rounds.iter().map(|r| r.rotation).collect();
+        assert_eq!(rotations, vec![1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1],
+                   "Rotation schedule");
+    }
+
+    #[test]
+    fn test_trace_encryption() {
+        let (_, block) = trace_encryption(0x0123456789ABCDEF, 0x133457799BBCDFF1, 16);
+        assert_eq!(block.initial_permutation, 0xCC00CCFFF0AAF0AA, "IP");
+        let first = &block.rounds[0];
+        assert_eq!((first.left, first.right), (0xCC00CCFF, 0xF0AAF0AA), "L0 R0");
+        assert_eq!(first.expansion, 0x7A15557A1555, "E(R0)");
+        assert_eq!(first.sbox_inputs,
+                   [0b011000, 0b010001, 0b011110, 0b111010, 0b100001, 0b100110, 0b010100, 0b100111],
+                   "S-box inputs of the first round");
+        assert_eq!(first.sbox_outputs, [0x5, 0xC, 0x8, 0x2, 0xB, 0x5, 0x9, 0x7],
+                   "S-box outputs of the first round");
+        assert_eq!(first.permutation, 0x234AA9BB, "f(R0, K1)");
+        assert_eq!(first.new_right, 0xEF4A6544, "R1");
+        assert_eq!(block.preoutput, 0x0A4CD99543423234, "R16 L16");
+        assert_eq!(block.output, 0x85E813540F0AB405, "Ciphertext");
+    }
+
+    #[test]
+    fn test_trace_decryption() {
+        let (_, block) = trace_decryption(0x85E813540F0AB405, 0x133457799BBCDFF1, 16);
+        assert_eq!(block.output, 0x0123456789ABCDEF, "Decrypted plaintext");
+        let (_, block) = trace_encryption(0x0123456789ABCDEF, 0x133457799BBCDFF1, 6);
+        let (_, reverse) = trace_decryption(block.output, 0x133457799BBCDFF1, 6);
+        assert_eq!(reverse.output, 0x0123456789ABCDEF, "Reduced round trace roundtrip");
+    }
+}
@@ -0,0 +1,269 @@
+//! Worked example of DES on a single block, in the manner of the
+//! classic "DES step by step" walkthrough. Every stage of the key
+//! schedule and of the encryption is printed in binary, with bits
+//! grouped the same way as the spec tables list them (e.g. by 7 after
+//! PC-1, by 6 after PC-2 and E, by 4 after S-boxes and P)
+
+use crate::des::trace::{self, BlockTrace, KeyScheduleTrace};
+use crate::des::api::CipherDefinition;
+use crate::des::sdes;
+
+const LABEL_WIDTH: usize = 12;
+const INNER_KEY_HALF_BITS: u32 = 28;
+
+/// Format of the produced document
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Markdown,
+}
+
+/// Explains encryption of a ‘block’ with a ‘key’ by DES reduced or
+/// extended to ‘rounds’ rounds (16 for standard DES)
+/// @returns document with every stage of the algorithm
+pub fn explain(key: u64, block: u64, rounds: usize, format: Format) -> String {
+    let (schedule, block_trace) = trace::trace_encryption(block, key, rounds);
+    explain_traces("DES step by step", key, block, rounds, &schedule, &block_trace, format)
+}
+
+/// Explains encryption of a ‘block’ with a ‘key’ by the DES-like cipher
+/// of the ‘definition’ with ‘rounds’ rounds
+/// @returns document with every stage of the algorithm
+pub fn explain_with_definition(key: u64, block: u64, rounds: usize,
+                               definition: &CipherDefinition, format: Format) -> String {
+    let (schedule, block_trace) = definition.trace_encryption(block, key, rounds);
+    explain_traces("Defined cipher step by step", key, block, rounds,
+                   &schedule, &block_trace, format)
+}
+
+fn explain_traces(title: &str, key: u64, block: u64, rounds: usize, schedule: &KeyScheduleTrace,
+                  block_trace: &BlockTrace, format: Format) -> String {
+    let mut document = Document { format, text: String::new() };
+    document.heading(1, title);
+    document.values(&[
+        ("Key", format!("{:#018X}", key)),
+        ("Block", format!("{:#018X}", block)),
+        ("Rounds", rounds.to_string()),
+    ]);
+    explain_key_schedule(&mut document, schedule);
+    explain_block(&mut document, block_trace);
+    document.text
+}
+
+fn explain_key_schedule(document: &mut Document, schedule: &KeyScheduleTrace) {
+    document.heading(2, "Key schedule");
+    let permuted = ((schedule.c0 as u64) << INNER_KEY_HALF_BITS) | schedule.d0 as u64;
+    document.values(&[
+        ("K", bits(schedule.key, 64, 8)),
+        ("PC-1(K)", bits(permuted, 56, 7)),
+        ("C0", bits(schedule.c0 as u64, 28, 7)),
+        ("D0", bits(schedule.d0 as u64, 28, 7)),
+    ]);
+    for (i, round) in schedule.rounds.iter().enumerate() {
+        let n = i + 1;
+        document.heading(3, &format!("Round key {} (halves rotated left by {})",
+                                     n, round.rotation));
+        document.values(&[
+            (&format!("C{}", n), bits(round.c as u64, 28, 7)),
+            (&format!("D{}", n), bits(round.d as u64, 28, 7)),
+            (&format!("K{}", n), bits(round.subkey.value, 48, 6)),
+        ]);
+    }
+}
+
+fn explain_block(document: &mut Document, block: &BlockTrace) {
+    document.heading(2, "Initial permutation");
+    document.values(&[
+        ("M", bits(block.input, 64, 4)),
+        ("IP(M)", bits(block.initial_permutation, 64, 4)),
+        ("L0", bits(block.initial_permutation >> 32, 32, 4)),
+        ("R0", bits(block.initial_permutation, 32, 4)),
+    ]);
+    for (i, round) in block.rounds.iter().enumerate() {
+        let n = i + 1;
+        document.heading(2, &format!("Round {}", n));
+        let mixed = round.expansion ^ round.subkey.value;
+        let sbox_output = round.sbox_outputs.iter()
+            .fold(0, |merged, &output| (merged << 4) | output as u64);
+        let mut values = vec![
+            (format!("E(R{})", i), bits(round.expansion, 48, 6)),
+            (format!("K{}", n), bits(round.subkey.value, 48, 6)),
+            (format!("K{}+E(R{})", n, i), bits(mixed, 48, 6)),
+        ];
+        for (s, (input, output)) in round.sbox_inputs.iter()
+            .zip(round.sbox_outputs.iter()).enumerate() {
+            values.push((format!("S{}", s + 1),
+                         format!("{} -> {}", bits(*input as u64, 6, 6),
+                                 bits(*output as u64, 4, 4))));
+        }
+        values.push(("S-boxes".to_string(), bits(sbox_output, 32, 4)));
+        values.push(("f = P(..)".to_string(), bits(round.permutation as u64, 32, 4)));
+        values.push((format!("L{}", n), bits(round.new_left as u64, 32, 4)));
+        values.push((format!("R{}", n), bits(round.new_right as u64, 32, 4)));
+        document.values(&values);
+    }
+    let last = block.rounds.len();
+    document.heading(2, "Reverse permutation");
+    document.values(&[
+        (format!("R{}L{}", last, last), bits(block.preoutput, 64, 8)),
+        ("IP⁻¹".to_string(), bits(block.output, 64, 8)),
+        ("Ciphertext".to_string(), format!("{:#018X}", block.output)),
+    ]);
+}
+
+/// Explains encryption of a ‘block’ with a ‘key’ by Simplified DES,
+/// bits are grouped by halves
+/// @returns document with every stage of the algorithm
+pub fn explain_sdes(key: u16, block: u8, format: Format) -> String {
+    let trace = sdes::trace_encryption(block, key);
+    let mut document = Document { format, text: String::new() };
+    document.heading(1, "S-DES step by step");
+    document.values(&[
+        ("Key", bits(key as u64, 10, 5)),
+        ("Block", bits(block as u64, 8, 4)),
+    ]);
+    document.heading(2, "Key schedule");
+    let schedule = &trace.key;
+    document.values(&[
+        ("K", bits(schedule.key as u64, 10, 5)),
+        ("P10(K)", bits(schedule.p10 as u64, 10, 5)),
+        ("LS-1", bits(schedule.shifted[0] as u64, 10, 5)),
+        ("K1 = P8(..)", bits(schedule.round_keys[0] as u64, 8, 4)),
+        ("LS-2", bits(schedule.shifted[1] as u64, 10, 5)),
+        ("K2 = P8(..)", bits(schedule.round_keys[1] as u64, 8, 4)),
+    ]);
+    document.heading(2, "Initial permutation");
+    document.values(&[
+        ("M", bits(trace.input as u64, 8, 4)),
+        ("IP(M)", bits(trace.initial_permutation as u64, 8, 4)),
+    ]);
+    for (i, round) in trace.rounds.iter().enumerate() {
+        let n = i + 1;
+        let title = if i == 0 { format!("fK{}", n) } else { format!("SW, then fK{}", n) };
+        document.heading(2, &title);
+        document.values(&[
+            ("L".to_string(), bits(round.left as u64, 4, 4)),
+            ("R".to_string(), bits(round.right as u64, 4, 4)),
+            ("E/P(R)".to_string(), bits(round.expansion as u64, 8, 4)),
+            (format!("K{}", n), bits(round.subkey as u64, 8, 4)),
+            ("S0".to_string(), format!("{} -> {}", bits(round.sbox_inputs[0] as u64, 4, 4),
+                                       bits(round.sbox_outputs[0] as u64, 2, 2))),
+            ("S1".to_string(), format!("{} -> {}", bits(round.sbox_inputs[1] as u64, 4, 4),
+                                       bits(round.sbox_outputs[1] as u64, 2, 2))),
+            ("F = P4(..)".to_string(), bits(round.permutation as u64, 4, 4)),
+            (format!("fK{}", n), bits(round.output as u64, 8, 4)),
+        ]);
+    }
+    document.heading(2, "Reverse permutation");
+    document.values(&[
+        ("IP⁻¹".to_string(), bits(trace.output as u64, 8, 4)),
+        ("Ciphertext".to_string(), format!("{:#04X}", trace.output)),
+    ]);
+    document.text
+}
+
+/// @returns ‘size_bits’ lowest bits of a ‘value’, MSB first, split by
+/// spaces into groups of ‘group’ bits
+fn bits(value: u64, size_bits: u32, group: u32) -> String {
+    let mut text = String::new();
+    for bit in (0..size_bits).rev() {
+        text.push(if (value >> bit) & 1 == 1 { '1' } else { '0' });
+        if bit != 0 && bit % group == 0 {
+            text.push(' ');
+        }
+    }
+    text
+}
+
+/// Document being built, in a given format
+struct Document {
+    format: Format,
+    text: String,
+}
+
+impl Document {
+    fn heading(&mut self, level: usize, title: &str) {
+        match self.format {
+            Format::Markdown => {
+                self.text += &format!("{} {}\n\n", "#".repeat(level), title);
+            },
+            Format::Text => {
+                let underline = match level { 1 => '=', 2 => '-', _ => '.' };
+                self.text += &format!("{}\n{}\n\n", title,
+                                      underline.to_string().repeat(title.chars().count()));
+            },
+        }
+    }
+
+    /// Labeled values, aligned in a column (a code block in Markdown)
+    fn values<L: AsRef<str>>(&mut self, values: &[(L, String)]) {
+        if self.format == Format::Markdown { self.text += "```\n"; }
+        for (label, value) in values.iter() {
+            self.text += &format!("{:<width$} = {}\n", label.as_ref(), value,
+                                  width = LABEL_WIDTH);
+        }
+        if self.format == Format::Markdown { self.text += "```\n"; }
+        self.text += "\n";
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bits_grouping() {
+        assert_eq!(bits(0xF0CCAAF, 28, 7), "1111000 0110011 0010101 0101111");
+        assert_eq!(bits(0b_011000_010001, 12, 6), "011000 010001");
+        assert_eq!(bits(0x5, 4, 4), "0101");
+    }
+
+    #[test]
+    fn test_explain_classic_example() {
+        let text = explain(0x133457799BBCDFF1, 0x0123456789ABCDEF, 16, Format::Text);
+        assert!(text.contains(
+            "PC-1(K)      = 1111000 0110011 0010101 0101111 \
+             0101010 1011001 1001111 0001111"), "PC-1 stage");
+        assert!(text.contains(
+            "K1           = 000110 110000 001011 101111 111111 000111 000001 110010"),
+            "First round key");
+        assert!(text.contains(
+            "E(R0)        = 011110 100001 010101 010101 011110 100001 010101 010101"),
+            "Expansion of the first round");
+        assert!(text.contains("S1           = 011000 -> 0101"), "First S-box");
+        assert!(text.contains(
+            "f = P(..)    = 0010 0011 0100 1010 1010 1001 1011 1011"),
+            "Feistel function of the first round");
+        assert!(text.contains("Ciphertext   = 0x85E813540F0AB405"), "Ciphertext");
+
+        let markdown = explain(0x133457799BBCDFF1, 0x0123456789ABCDEF, 16, Format::Markdown);
+        assert!(markdown.starts_with("# DES step by step"), "Markdown title");
+        assert!(markdown.contains("## Round 16"), "Markdown round heading");
+        assert_eq!(markdown.matches("```").count() % 2, 0, "Unclosed code block");
+    }
+
+    #[test]
+    fn test_explain_definition() {
+        let definition = CipherDefinition::parse(include_str!("../../bin/des.def"))
+            .expect("Valid DES definition");
+        let text = explain_with_definition(
+            0x133457799BBCDFF1, 0x0123456789ABCDEF, 16, &definition, Format::Text);
+        assert!(text.starts_with("Defined cipher step by step"), "Title");
+        let des_text = explain(0x133457799BBCDFF1, 0x0123456789ABCDEF, 16, Format::Text);
+        // past the title and its underline
+        assert!(text.lines().skip(2).eq(des_text.lines().skip(2)),
+                "DES definition should be explained as DES");
+    }
+
+    #[test]
+    fn test_explain_sdes() {
+        let text = explain_sdes(0b10100_00010, 0b1001_0111, Format::Text);
+        assert!(text.contains("P10(K)       = 10000 01100"), "P10 stage");
+        assert!(text.contains("K1 = P8(..)  = 1010 0100"), "First round key");
+        assert!(text.contains("K2 = P8(..)  = 0100 0011"), "Second round key");
+        assert!(text.contains("IP(M)        = 0101 1101"), "Initial permutation");
+        assert!(text.contains("IP⁻¹         = 0011 1000"), "Ciphertext bits");
+        assert!(text.contains("Ciphertext   = 0x38"), "Ciphertext");
+    }
+}
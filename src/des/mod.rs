@@ -4,6 +4,13 @@ pub mod api;
 pub use self::api::*;
 
 mod details;
+pub use self::details::trace;
+
+pub mod benchmark;
+pub mod explain;
+pub mod crack;
+pub mod sdes;
+pub mod analysis;
 
 
 
@@ -0,0 +1,280 @@
+//! Simplified DES (E. Schaefer, 1996), a teaching cipher with 10 bit
+//! key and 8 bit block. It has the structure of DES in miniature:
+//! initial permutation, two Feistel rounds with expansion, two S-boxes
+//! and permutation P4, halves switched between the rounds, and inverse
+//! of the initial permutation. Two 8 bit round keys are picked by P8
+//! from the key, which halves are rotated. Key space is so small, that
+//! the exhaustive search is instant
+
+use crate::math::bit_arithmetics::idx_from_low as low;
+use crate::des::details::{EncodingTable, PermutationTable};
+
+const BIT_COUNT_FROM: u32 = 1;
+pub const KEY_BITS: u32 = 10;
+pub const BLOCK_BITS: u32 = 8;
+const BITS_IN_HALF: u32 = 4;
+const KEY_HALF_BITS: u32 = 5;
+const SBOX_INPUT_BITS: u32 = 4;
+const SBOX_OUTPUT_BITS: u32 = 2;
+const SBOX_ROW_BITS_INDICES: [u32; 2] = [0, 3];
+/// Rotations of the key halves before each round key
+const ROTATIONS: [u32; 2] = [1, 2];
+
+static P10: PermutationTable = PermutationTable::new(&[
+    3, 5, 2, 7, 4, 10, 1, 9, 8, 6,
+], BIT_COUNT_FROM, KEY_BITS);
+
+static P8: PermutationTable = PermutationTable::new(&[
+    6, 3, 7, 4, 8, 5, 10, 9,
+], BIT_COUNT_FROM, KEY_BITS);
+
+static INITIAL_PERMUTATION: PermutationTable = PermutationTable::new(&[
+    2, 6, 3, 1, 4, 8, 5, 7,
+], BIT_COUNT_FROM, BLOCK_BITS);
+
+static REVERSE_PERMUTATION: PermutationTable = PermutationTable::new(&[
+    4, 1, 3, 5, 7, 2, 8, 6,
+], BIT_COUNT_FROM, BLOCK_BITS);
+
+/// Expansion / permutation of a 4 bit half to 8 bits (E/P)
+static EXPANSION: PermutationTable = PermutationTable::new(&[
+    4, 1, 2, 3, 2, 3, 4, 1,
+], BIT_COUNT_FROM, BITS_IN_HALF);
+
+static P4: PermutationTable = PermutationTable::new(&[
+    2, 4, 3, 1,
+], BIT_COUNT_FROM, BITS_IN_HALF);
+
+/// S0 and S1, each matches 4 bits to a 2 bit number (the row is chosen
+/// by the outer bits, the column by the inner two)
+static SBOXES: [EncodingTable<'static>; 2] = [
+    EncodingTable::new(
+        &[
+            1, 0, 3, 2,
+            3, 2, 1, 0,
+            0, 2, 1, 3,
+            3, 1, 3, 2,
+        ],
+        &SBOX_ROW_BITS_INDICES,
+        SBOX_INPUT_BITS,
+        SBOX_OUTPUT_BITS,
+    ),
+    EncodingTable::new(
+        &[
+            0, 1, 2, 3,
+            2, 0, 1, 3,
+            3, 0, 1, 0,
+            2, 1, 0, 3,
+        ],
+        &SBOX_ROW_BITS_INDICES,
+        SBOX_INPUT_BITS,
+        SBOX_OUTPUT_BITS,
+    ),
+];
+
+/// Rotates both 5 bit halves of a 10 bit key to the left
+/// @returns rotated key
+fn rotate_halves(key: u16, shift: u32) -> u16 {
+    let key = low::rotate_range_to_high(key as u64, 0, KEY_HALF_BITS, shift, KEY_BITS).unwrap();
+    low::rotate_range_to_high(key, KEY_HALF_BITS, KEY_BITS, shift, KEY_BITS).unwrap() as u16
+}
+
+/// @returns round keys K1 and K2 of the 10 bit ‘key’
+pub fn round_keys(key: u16) -> [u8; 2] {
+    let mut inner_key = P10.apply(key as u64) as u16;
+    let mut keys = [0; 2];
+    for (round_key, &shift) in keys.iter_mut().zip(ROTATIONS.iter()) {
+        inner_key = rotate_halves(inner_key, shift);
+        *round_key = P8.apply(inner_key as u64) as u8;
+    }
+    keys
+}
+
+/// Obscuring function F, operating on 4 bit half with a round key
+/// @returns obscured with the key half
+fn feistel_function(half: u8, key: u8) -> u8 {
+    let mixed = EXPANSION.apply(half as u64) ^ key as u64;
+    let merged = (SBOXES[0].apply(mixed >> SBOX_INPUT_BITS) << SBOX_OUTPUT_BITS)
+        | SBOXES[1].apply(mixed & ((1 << SBOX_INPUT_BITS) - 1));
+    P4.apply(merged) as u8
+}
+
+/// Function fK: XORs the left half with F of the right half
+/// @returns block with the right half unchanged
+fn mix_halves(block: u8, key: u8) -> u8 {
+    let (left, right) = (block >> BITS_IN_HALF, block & 0xF);
+    ((left ^ feistel_function(right, key)) << BITS_IN_HALF) | right
+}
+
+/// Transforms a block with round keys in a given order
+fn act_on_block(block: u8, keys: [u8; 2]) -> u8 {
+    let block = INITIAL_PERMUTATION.apply(block as u64) as u8;
+    let block = mix_halves(block, keys[0]).rotate_left(BITS_IN_HALF);
+    let block = mix_halves(block, keys[1]);
+    REVERSE_PERMUTATION.apply(block as u64) as u8
+}
+
+/// Takes an 8 bit block, encrypts it with a 10 bit ‘key’
+/// @returns encrypted block
+pub fn encrypt_block(block: u8, key: u16) -> u8 {
+    act_on_block(block, round_keys(key))
+}
+
+/// Takes an 8 bit block, decrypts it with a 10 bit ‘key’
+/// @returns decrypted block
+pub fn decrypt_block(block: u8, key: u16) -> u8 {
+    let [first, second] = round_keys(key);
+    act_on_block(block, [second, first])
+}
+
+/// Every 8 bit block is encrypted (or decrypted) by the ‘key’ at once
+/// @returns lookup table of the cipher
+pub fn codebook(key: u16, decrypt: bool) -> [u8; 1 << BLOCK_BITS] {
+    let mut codebook = [0; 1 << BLOCK_BITS];
+    for (block, output) in codebook.iter_mut().enumerate() {
+        *output = if decrypt { decrypt_block(block as u8, key) }
+                  else { encrypt_block(block as u8, key) };
+    }
+    codebook
+}
+
+/// Exhaustive key search: tries every key on the known pairs of
+/// plaintext and ciphertext. A single pair usually leaves a few keys
+/// (the block is shorter than the key)
+/// @returns every key, which encrypts the plaintexts into ciphertexts
+pub fn search_keys(pairs: &[(u8, u8)]) -> Vec<u16> {
+    (0..1 << KEY_BITS)
+        .filter(|&key| pairs.iter().all(|&(p, c)| encrypt_block(p, key) == c))
+        .collect()
+}
+
+/// State of the key schedule
+#[derive(Clone, Debug)]
+pub struct KeyTrace {
+    pub key: u16,
+    pub p10: u16,
+    /// Key after the rotations before each round key
+    pub shifted: [u16; 2],
+    pub round_keys: [u8; 2],
+}
+
+/// A single application of fK
+#[derive(Clone, Debug)]
+pub struct RoundTrace {
+    pub left: u8,
+    pub right: u8,
+    pub subkey: u8,
+    /// Right half, expanded to 8 bits (E/P)
+    pub expansion: u8,
+    /// 4 bit inputs of S0 and S1 (expansion XOR round key)
+    pub sbox_inputs: [u8; 2],
+    /// 2 bit outputs of S0 and S1
+    pub sbox_outputs: [u8; 2],
+    /// S-boxes output after P4 (i.e. result of F)
+    pub permutation: u8,
+    /// Block after fK (left half XOR F, right half unchanged)
+    pub output: u8,
+}
+
+/// Whole encryption of a block
+#[derive(Clone, Debug)]
+pub struct BlockTrace {
+    pub key: KeyTrace,
+    pub input: u8,
+    pub initial_permutation: u8,
+    /// Applications of fK, the halves are switched between them
+    pub rounds: Vec<RoundTrace>,
+    pub output: u8,
+}
+
+/// Traces encryption of a ‘block’ with a ‘key’ step by step
+/// @returns every intermediate value
+pub fn trace_encryption(block: u8, key: u16) -> BlockTrace {
+    let p10 = P10.apply(key as u64) as u16;
+    let shifted_once = rotate_halves(p10, ROTATIONS[0]);
+    let shifted = [shifted_once, rotate_halves(shifted_once, ROTATIONS[1])];
+    let key_trace = KeyTrace { key, p10, shifted, round_keys: round_keys(key) };
+
+    let initial_permutation = INITIAL_PERMUTATION.apply(block as u64) as u8;
+    let mut data = initial_permutation;
+    let mut rounds = vec![];
+    for (idx, &subkey) in key_trace.round_keys.iter().enumerate() {
+        if idx > 0 {
+            data = data.rotate_left(BITS_IN_HALF);
+        }
+        let (left, right) = (data >> BITS_IN_HALF, data & 0xF);
+        let expansion = EXPANSION.apply(right as u64) as u8;
+        let mixed = expansion ^ subkey;
+        let sbox_inputs = [mixed >> SBOX_INPUT_BITS, mixed & 0xF];
+        let sbox_outputs = [SBOXES[0].apply(sbox_inputs[0] as u64) as u8,
+                            SBOXES[1].apply(sbox_inputs[1] as u64) as u8];
+        let permutation = P4.apply(((sbox_outputs[0] << SBOX_OUTPUT_BITS)
+                                    | sbox_outputs[1]) as u64) as u8;
+        data = ((left ^ permutation) << BITS_IN_HALF) | right;
+        rounds.push(RoundTrace {
+            left, right, subkey, expansion, sbox_inputs, sbox_outputs, permutation,
+            output: data,
+        });
+    }
+    BlockTrace {
+        key: key_trace,
+        input: block,
+        initial_permutation,
+        rounds,
+        output: REVERSE_PERMUTATION.apply(data as u64) as u8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (key, plaintext, ciphertext) of the textbook examples
+    const VECTORS: [(u16, u8, u8); 5] = [
+        (0b10100_00010, 0b1001_0111, 0b0011_1000),
+        (0b00000_00000, 0b1010_1010, 0b0001_0001),
+        (0b11100_01110, 0b1010_1010, 0b1100_1010),
+        (0b11100_01110, 0b0101_0101, 0b0111_0000),
+        (0b11111_11111, 0b1010_1010, 0b0000_0100),
+    ];
+
+    #[test]
+    fn test_round_keys() {
+        assert_eq!(round_keys(0b10100_00010), [0b1010_0100, 0b0100_0011],
+                   "Round keys of the textbook example");
+    }
+
+    #[test]
+    fn test_vectors() {
+        for &(key, plaintext, ciphertext) in VECTORS.iter() {
+            assert_eq!(encrypt_block(plaintext, key), ciphertext,
+                       "Encryption of {:08b} with {:010b}", plaintext, key);
+            assert_eq!(decrypt_block(ciphertext, key), plaintext,
+                       "Decryption of {:08b} with {:010b}", ciphertext, key);
+        }
+        let codebook = codebook(0b10100_00010, false);
+        let inverse = super::codebook(0b10100_00010, true);
+        assert!((0..=255).all(|block| inverse[codebook[block] as usize] == block as u8),
+                "Decryption codebook should invert the encryption one");
+    }
+
+    #[test]
+    fn test_trace() {
+        let trace = trace_encryption(0b1001_0111, 0b10100_00010);
+        assert_eq!(trace.key.p10, 0b10000_01100, "P10 of the textbook example");
+        assert_eq!(trace.key.round_keys, [0b1010_0100, 0b0100_0011]);
+        assert_eq!(trace.rounds.len(), 2, "fK is applied twice");
+        assert_eq!(trace.output, 0b0011_1000, "Trace should agree with encryption");
+    }
+
+    #[test]
+    fn test_search_keys() {
+        for &(key, plaintext, ciphertext) in VECTORS.iter() {
+            let keys = search_keys(&[(plaintext, ciphertext)]);
+            assert!(keys.contains(&key), "Key {:010b} should be found", key);
+        }
+        let key = 0b10100_00010;
+        let pairs: Vec<(u8, u8)> = (0..=255).map(|p| (p, encrypt_block(p, key))).collect();
+        assert_eq!(search_keys(&pairs), vec![key], "Whole codebook leaves the only key");
+    }
+}
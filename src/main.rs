@@ -26,8 +26,6 @@
 //! info
 
 #![allow(dead_code, unused)]
-#[macro_use]
-extern crate lazy_static;
 
 mod math;
 mod des;
@@ -44,6 +42,7 @@ fn main() {
     // a better one
     use cli::Cli;
     use cli::Action;
+    use cli::{Logger, MessagingLevel};
     
     let cli = Cli::new()
         .default_key("FFFF-0000-FFFF-0000")
@@ -53,36 +52,532 @@ fn main() {
         .parse_args(std::env::args());
 
     // Error in the hardcoded build of CLI  
-    if let None = cli {
-        Cli::print_usage();
+    if cli.is_none() {
+        Cli::print_usage(&mut Logger::stderr(MessagingLevel::Normal));
         return;
     }
     
     let cli = cli.unwrap();
+    let mut logger = Logger::stderr(cli.messaging_level());
 
     // Show help, if --help flag is present, no matter what are other
     // flags and command line arguments
     if !cli.help_requested {
-        cli.announce_begin();
+        match cli.action() {
+            Action::Benchmark => run_benchmark(&cli),
+            Action::Explain => explain_block(&cli, &mut logger),
+            Action::Analyze => run_analysis(&cli, &mut logger),
+            Action::Attack => run_attack(&cli, &mut logger),
+            Action::Crack => crack_key(&cli, &mut logger),
+            _ if cli.recursive() => transform_directory(&cli, &mut logger),
+            _ if !cli.batch_paths().is_empty() => transform_batch(&cli, &mut logger),
+            _ => transform_file(&cli, &mut logger),
+        }
+    } else {
+        Cli::print_help(&mut logger);
+    }
+    
+}
+
+/// Loads the tables of the cipher, if they’re given in command line,
+/// exits on errors in them
+/// @returns parsed definition of the cipher
+fn load_definition<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>)
+                                      -> Option<des::api::CipherDefinition> {
+    cli.cipher_def_path().map(|path| {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) => {
+                logger.always(format_args!("{} {}: {}", cli.tag(), path.display(), error));
+                std::process::exit(1);
+            },
+        };
+        match des::api::CipherDefinition::parse(&text) {
+            Ok(definition) => definition,
+            Err(error) => {
+                logger.always(format_args!("{} {}", cli.tag(), error));
+                std::process::exit(1);
+            },
+        }
+    })
+}
+
+/// User output: cipher, mode and tables, which transform the data
+fn announce_mode<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    let tag = cli.tag();
+    if cli.cipher() == cli::Cipher::SDes {
+        logger.verbose(format_args!("{} Mode: S-DES-ECB, 8 bit blocks, no padding", tag));
+    } else {
+        let byte_order = match cli.endianess() {
+            reinterpret_bytes::Endianess::Big => "big-endian",
+            reinterpret_bytes::Endianess::Little => "little-endian",
+        };
+        logger.verbose(format_args!("{} Mode: {}-ECB, {} rounds, {} thread(s), zero padding, {} blocks",
+                                    tag, cli.cipher().name(), cli.rounds(), cli.threads().unwrap_or(1),
+                                    byte_order));
+    }
+    if let Some(path) = cli.cipher_def_path() {
+        logger.verbose(format_args!("{} Tables: {}", tag, path.display()));
+    }
+}
+
+/// Builds the cipher (its round keys), as specified in command line,
+/// once for every stream it transforms
+/// @returns cipher, ready to encrypt / decrypt
+fn prepare_cipher<'a>(cli: &cli::Cli, definition: Option<&'a des::api::CipherDefinition>)
+                      -> des::api::PreparedCipher<'a> {
+    use cli::Action;
+    use des::api::PreparedCipher;
+
+    let threads = cli.threads().unwrap_or(1);
+    let encrypting = match cli.action() {
+        Action::EncryptFile => true,
+        Action::DecryptFile => false,
+        Action::TripleEncryptFile | Action::TripleDecryptFile | Action::Benchmark
+            | Action::Explain | Action::Analyze | Action::Attack | Action::Crack =>
+            unreachable!("Only encryption / decryption processes files"),
+    };
+    match (cli.cipher(), definition) {
+        (cli::Cipher::SDes, _) => PreparedCipher::sdes(cli.key() as u16, encrypting),
+        (cli::Cipher::DesX, _) => PreparedCipher::desx(
+            cli.whitening().map(|(pre, post)| des::api::DesXKey::new(cli.key(), pre, post))
+                .expect("DES-X key is required by command line"),
+            encrypting, cli.endianess(), cli.rounds(), threads),
+        (cli::Cipher::Des, Some(definition)) => PreparedCipher::with_definition(
+            cli.key(), definition, encrypting, cli.endianess(), cli.rounds(), threads),
+        (cli::Cipher::Des, None) => PreparedCipher::des(
+            cli.key(), encrypting, cli.endianess(), cli.rounds(), threads),
+    }
+}
 
-        // Open read file, open / create write file
-        let (read, write) = data_io::open_rw_files(
+/// Encrypts / decrypts the file, as specified in command line
+fn transform_file<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    use cli::Action;
+
+    cli.announce_begin(logger);
+    let tag = cli.tag();
+    let definition = load_definition(cli, logger);
+    announce_mode(cli, logger);
+
+    let cipher = prepare_cipher(cli, definition.as_ref());
+    let transform_counted = |read: &mut dyn std::io::Read, write: &mut dyn std::io::Write| {
+        let mut read = data_io::Counted::new(read);
+        let mut write = data_io::Counted::new(write);
+        cipher.transform(&mut read, &mut write).map(|_| (read.transferred(), write.transferred()))
+    };
+    let start = std::time::Instant::now();
+
+    let (read_bytes, written_bytes) = if cli.in_place() {
+        // The original file is untouched on errors
+        let mut transferred = (0, 0);
+        let result = data_io::transform_in_place(cli.src_file_path(), |read, write| {
+            transferred = transform_counted(read, write)?;
+            Ok(())
+        });
+        match result {
+            Ok(None) => (),
+            Ok(Some(error)) => logger.always(format_args!(
+                "{} {}: <replaced, but the directory isn't synced> {}",
+                tag, cli.src_file_path().display(), error)),
+            Err(error) => {
+                logger.always(format_args!("{} {}: {}", tag, cli.src_file_path().display(), error));
+                std::process::exit(1);
+            },
+        }
+        transferred
+    } else {
+        // Open read file, open / create write file (or standard streams)
+        let (mut read, mut write) = match data_io::open_rw_streams(
             cli.src_file_path(),
-            cli.dst_file_path()
-        ).expect("Failed I/O operation.");
+            cli.dst_file_path(),
+            cli.force()
+        ) {
+            Ok(streams) => streams,
+            Err(error) => {
+                logger.always(format_args!("{} {}", tag, error));
+                std::process::exit(1);
+            },
+        };
+        match transform_counted(&mut read, &mut write) {
+            Ok(transferred) => transferred,
+            // The reader of the output is gone (e.g. `des ... | head`),
+            // nothing is left to do
+            Err(error) if error.kind() == std::io::ErrorKind::BrokenPipe =>
+                std::process::exit(0),
+            Err(error) => {
+                logger.always(format_args!("{} {}: {}", tag, cli.src_file_path().display(), error));
+                std::process::exit(1);
+            },
+        }
+    };
 
-        match cli.action() {
-            Action::EncryptFile => des::api::encrypt(read, write, cli.key(), cli.endianess()),
-            Action::DecryptFile => des::api::decrypt(read, write, cli.key(), cli.endianess()),
-            Action::TripleEncryptFile => des::api::triple_encrypt(
-                read, write, (cli.key(), cli.key(), cli.key()), cli.endianess()),
-            Action::TripleDecryptFile => des::api::triple_decrypt(
-                read, write, (cli.key(), cli.key(), cli.key()), cli.endianess()),
-        }.expect("Internal error (I/O)");
-        
-        cli.announce_end();    
+    let elapsed = start.elapsed();
+    logger.verbose(format_args!("{} Read {} bytes, wrote {} bytes",
+                                tag, read_bytes, written_bytes));
+    if let Action::EncryptFile | Action::TripleEncryptFile = cli.action() {
+        logger.verbose(format_args!("{} Padding: {} zero bytes",
+                                    tag, written_bytes.saturating_sub(read_bytes)));
+    }
+    logger.verbose(format_args!(
+        "{} Elapsed: {:.3} s ({:.2} MB/s)", tag, elapsed.as_secs_f64(),
+        read_bytes as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64().max(f64::EPSILON)));
+    cli.announce_end(logger);
+}
+
+/// Encrypts / decrypts every source file of ‘jobs’ into its
+/// destination file by the same ‘cipher’, reports each one. Failed
+/// files don’t stop the others
+/// @returns numbers of done and failed files
+fn transform_each<W, I>(cli: &cli::Cli, logger: &mut cli::Logger<W>,
+                        cipher: &des::api::PreparedCipher, jobs: I) -> (usize, usize)
+where W: std::io::Write, I: IntoIterator<Item=(std::path::PathBuf, std::path::PathBuf)> {
+    let tag = cli.tag();
+    let (mut done, mut failed) = (0, 0);
+    for (src_path, dst_path) in jobs {
+        let result = dst_path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| Ok(data_io::open_rw_files(&src_path, &dst_path, cli.force())?))
+            .and_then(|(read, write)| cipher.transform(read, write));
+        match result {
+            Ok(()) => {
+                done += 1;
+                logger.info(format_args!("{} {} -> {}", tag, src_path.display(), dst_path.display()));
+            },
+            Err(error) => {
+                failed += 1;
+                logger.always(format_args!("{} {}: {}", tag, src_path.display(), error));
+            },
+        }
+    }
+    (done, failed)
+}
+
+/// User output: numbers of done and failed files, exits with an error
+/// code, if any file failed
+fn announce_summary<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>,
+                                       done: usize, failed: usize) {
+    logger.always(format_args!("{} Summary: {} file(s) done, {} failed", cli.tag(), done, failed));
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Creates the output directory (with parents) or exits
+fn create_output_dir<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    if let Err(error) = std::fs::create_dir_all(cli.dst_file_path()) {
+        logger.always(format_args!("{} {}: {}", cli.tag(), cli.dst_file_path().display(), error));
+        std::process::exit(1);
+    }
+}
+
+/// Encrypts / decrypts every file of the source directory tree into
+/// the mirrored tree of the destination directory. Failed files are
+/// reported and skipped, the summary is printed in the end
+fn transform_directory<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    let tag = cli.tag();
+    let (src_root, dst_root) = (cli.src_file_path(), cli.dst_file_path());
+    logger.info(format_args!("{} Input  directory: {}", tag, src_root.display()));
+    logger.info(format_args!("{} Output directory: {}", tag, dst_root.display()));
+    logger.info(format_args!("{} Key = {:#018x}", tag, cli.key()));
+    let definition = load_definition(cli, logger);
+    announce_mode(cli, logger);
+
+    // Output tree inside of the input one would be walked into, it's
+    // checked before anything is created
+    match data_io::is_nested_dir(dst_root, src_root) {
+        Ok(false) => (),
+        Ok(true) => {
+            logger.always(format_args!("{} <output directory is inside of the input one>", tag));
+            std::process::exit(1);
+        },
+        Err(error) => {
+            logger.always(format_args!("{} {}", tag, error));
+            std::process::exit(1);
+        },
+    }
+    create_output_dir(cli, logger);
+
+    let encrypting = matches!(cli.action(), cli::Action::EncryptFile);
+    let (files, failures) = data_io::walk_files(src_root);
+    for (path, error) in failures.iter() {
+        logger.always(format_args!("{} {}: {}", tag, path.display(), error));
+    }
+    let jobs = files.into_iter().map(|src_path| {
+        let relative = src_path.strip_prefix(src_root).expect("Walk starts at the root");
+        let dst_path = dst_root.join(data_io::mirrored_path(&relative, encrypting));
+        (src_path, dst_path)
+    });
+    let cipher = prepare_cipher(cli, definition.as_ref());
+    let (done, failed) = transform_each(cli, logger, &cipher, jobs);
+    announce_summary(cli, logger, done, failed + failures.len());
+}
+
+/// Encrypts / decrypts every file of the batch (patterns are expanded)
+/// into the output directory with the same key schedule. Failed files
+/// are reported and skipped, the summary is printed in the end
+fn transform_batch<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    let tag = cli.tag();
+    let dst_root = cli.dst_file_path();
+    logger.info(format_args!("{} Output directory: {}", tag, dst_root.display()));
+    logger.info(format_args!("{} Key = {:#018x}", tag, cli.key()));
+    let definition = load_definition(cli, logger);
+    announce_mode(cli, logger);
+    create_output_dir(cli, logger);
+
+    // Two inputs of the same name can’t go to the same output
+    let encrypting = matches!(cli.action(), cli::Action::EncryptFile);
+    let mut jobs = vec![];
+    let mut taken = std::collections::HashSet::new();
+    let mut failures = 0;
+    let mut fail = |path: &std::path::Path, error: &dyn std::fmt::Display| {
+        logger.always(format_args!("{} {}: {}", tag, path.display(), error));
+        failures += 1;
+    };
+    for pattern in cli.batch_paths() {
+        let paths = match data_io::expand_pattern(pattern) {
+            Ok(paths) if paths.is_empty() => {
+                fail(pattern, &"<no files match the pattern>");
+                continue;
+            },
+            Ok(paths) => paths,
+            Err(error) => {
+                fail(pattern, &error);
+                continue;
+            },
+        };
+        for src_path in paths {
+            match src_path.file_name() {
+                Some(name) if !src_path.is_dir() => {
+                    let dst_path = dst_root.join(data_io::mirrored_path(&name, encrypting));
+                    if taken.insert(dst_path.clone()) {
+                        jobs.push((src_path, dst_path));
+                    } else {
+                        fail(&src_path, &format_args!("<{} is the output of another file>",
+                                                      dst_path.display()));
+                    }
+                },
+                _ => fail(&src_path, &"<not a file>"),
+            }
+        }
+    }
+    let cipher = prepare_cipher(cli, definition.as_ref());
+    let (done, failed) = transform_each(cli, logger, &cipher, jobs);
+    announce_summary(cli, logger, done, failed + failures);
+}
+
+/// Exhaustive search of S-DES key by a known pair, prints every key,
+/// which matches the pair
+fn crack_sdes_key<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    use des::sdes;
+    let plaintext = cli.plaintext().expect("Crack requires a plaintext") as u8;
+    let ciphertext = cli.ciphertext().expect("Crack requires a ciphertext") as u8;
+    logger.info(format_args!("{} Searching 2^{} keys of S-DES", cli.tag(), sdes::KEY_BITS));
+    let keys = sdes::search_keys(&[(plaintext, ciphertext)]);
+    for key in keys.iter() {
+        println!("Key found: {:010b}", key);
+    }
+    if keys.is_empty() {
+        println!("Key not found");
+        std::process::exit(1);
+    }
+}
+
+/// Measures throughput of DES and 3DES, prints the report as a table,
+/// and optionally writes it to a JSON file
+fn run_benchmark(cli: &cli::Cli) {
+    // Threaded engine runs on all the cores, unless told otherwise
+    let threads = cli.threads().unwrap_or_else(|| {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    });
+    let report = des::benchmark::run(cli.bench_size_mb() * 1024 * 1024, threads);
+    print!("{}", report.to_table());
+    if let Some(json_path) = cli.json_path() {
+        std::fs::write(json_path, report.to_json())
+            .expect("Failed I/O operation.");
+    }
+}
+
+/// Prints a step by step explanation of DES (or of the cipher with the
+/// tables, given in command line) encryption of the block
+fn explain_block<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    use des::explain::Format;
+    let format = if cli.markdown() { Format::Markdown } else { Format::Text };
+    let block = cli.block().expect("Explain action requires a block");
+    if cli.cipher() == cli::Cipher::SDes {
+        print!("{}", des::explain::explain_sdes(cli.key() as u16, block as u8, format));
+    } else if let Some(definition) = load_definition(cli, logger) {
+        print!("{}", des::explain::explain_with_definition(
+            cli.key(), block, cli.rounds(), &definition, format));
     } else {
-        Cli::print_help();
+        print!("{}", des::explain::explain(cli.key(), block, cli.rounds(), format));
+    }
+}
+
+/// Analyzes the parts of the cipher, requested in command line, prints
+/// the report, and optionally writes it to CSV / JSON files
+fn run_analysis<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    use des::analysis;
+    let definition = load_definition(cli, logger);
+    let sboxes = match (cli.sboxes_path(), definition) {
+        (Some(path), _) => {
+            let text = match std::fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(error) => {
+                    logger.always(format_args!("{} {}: {}", cli.tag(), path.display(), error));
+                    std::process::exit(1);
+                },
+            };
+            match analysis::parse_sboxes(&text) {
+                Ok(sboxes) => sboxes,
+                Err(error) => {
+                    logger.always(format_args!("{} {}", cli.tag(), error));
+                    std::process::exit(1);
+                },
+            }
+        },
+        (None, Some(definition)) => definition.sboxes().iter().map(analysis::sbox_of).collect(),
+        (None, None) => analysis::des_sboxes().to_vec(),
+    };
+    let mut exports = vec![];
+    if cli.ddt() {
+        let report = analysis::differential::analyze(&sboxes);
+        print!("{}", report.to_text());
+        exports.push((report.to_csv(), report.to_json()));
+    }
+    if cli.lat() {
+        let report = analysis::linear::analyze(&sboxes, cli.top());
+        print!("{}", report.to_text());
+        exports.push((report.to_csv(), report.to_json()));
+    }
+    // Command line doesn’t allow export of several reports at once
+    if let Some((csv, json)) = exports.pop() {
+        if let Some(csv_path) = cli.csv_path() {
+            std::fs::write(csv_path, csv).expect("Failed I/O operation.");
+        }
+        if let Some(json_path) = cli.json_path() {
+            std::fs::write(json_path, json).expect("Failed I/O operation.");
+        }
+    }
+}
+
+/// Demonstrates the cryptanalytic attack, requested in command line,
+/// on reduced round DES and prints its report
+fn run_attack<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    use des::analysis::{differential_attack, linear_attack, meet_in_the_middle};
+    let seed = cli.seed().unwrap_or_else(|| {
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64).unwrap_or(0)
+    });
+    logger.info(format_args!("{} Seed = {}", cli.tag(), seed));
+    match cli.attack_kind() {
+        Some(cli::AttackKind::Linear) => {
+            let pairs = if cli.pairs().is_empty() {
+                linear_attack::default_pair_counts(cli.rounds())
+            } else {
+                cli.pairs().to_vec()
+            };
+            let report = linear_attack::run(cli.rounds(), &pairs, cli.trials(), seed);
+            print!("{}", report.to_text());
+        },
+        Some(cli::AttackKind::Differential) => {
+            let pairs = cli.pairs().first().cloned();
+            let report = differential_attack::run(cli.rounds(), pairs, cli.trials(), seed);
+            print!("{}", report.to_text());
+        },
+        Some(cli::AttackKind::MeetInTheMiddle) => {
+            let bits = if cli.bits().is_empty() {
+                meet_in_the_middle::DEFAULT_BITS.to_vec()
+            } else {
+                cli.bits().to_vec()
+            };
+            let pairs = cli.pairs().first().cloned().unwrap_or(meet_in_the_middle::DEFAULT_PAIRS);
+            let report = meet_in_the_middle::run(&bits, pairs, cli.trials(), seed);
+            print!("{}", report.to_text());
+        },
+        None => unreachable!("Attack kind is required by command line"),
+    }
+}
+
+/// Searches the key by the known block, given in command line (or in
+/// the checkpoint to resume), reports progress on the way and saves it
+/// to the checkpoint file
+fn crack_key<W: std::io::Write>(cli: &cli::Cli, logger: &mut cli::Logger<W>) {
+    use des::crack::{self, Checkpoint, CrackConfig, KeySpace, KnownBlocks};
+    const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+    let tag = cli.tag();
+    if cli.cipher() == cli::Cipher::SDes {
+        return crack_sdes_key(cli, logger);
+    }
+    let mut fail = |message: &str| -> ! {
+        logger.always(format_args!("{} {}", tag, message));
+        std::process::exit(1);
+    };
+    let blocks = match (cli.plaintext(), cli.ciphertext()) {
+        (Some(plaintext), Some(ciphertext)) =>
+            Some(KnownBlocks { plaintext, ciphertext, complement: cli.complement() }),
+        _ => None,
+    };
+    let space = cli.key_mask().map(|mask| KeySpace::new(cli.key(), mask));
+    let checkpoint = match cli.checkpoint_path().filter(|path| path.is_file()) {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(text) => match Checkpoint::parse(&text) {
+                Ok(checkpoint) => Some(checkpoint),
+                Err(error) => fail(&error.to_string()),
+            },
+            Err(error) => fail(&format!("{}: {}", path.display(), error)),
+        },
+        None => None,
+    };
+    let (blocks, space, start_chunk) = match (blocks, checkpoint) {
+        (_, Some(checkpoint)) if blocks.is_some_and(|b| b != checkpoint.blocks)
+            || space.is_some_and(|s| s != checkpoint.space) =>
+            fail("<checkpoint is saved by a search of other blocks or keys>"),
+        (_, Some(checkpoint)) => (checkpoint.blocks, checkpoint.space, checkpoint.next_chunk),
+        (Some(blocks), None) => (blocks, space.unwrap_or_else(|| KeySpace::new(0, !0)), 0),
+        (None, None) => fail("<known blocks are required to start a search>"),
+    };
+    // Checkpoint is replaced atomically, so that a killed search can
+    // always be resumed. It’s saved before the search, so that a path,
+    // which can’t be written, is reported before any work is done
+    let save_checkpoint = |next_chunk| match cli.checkpoint_path() {
+        Some(path) => data_io::write_atomically(path, Checkpoint { blocks, space, next_chunk }
+                                                .to_text().as_bytes())
+            .map_err(|error| format!("{}: {}", path.display(), error)),
+        None => Ok(()),
+    };
+    if let Err(error) = save_checkpoint(start_chunk) {
+        fail(&error);
+    }
+    // Search runs on all the cores, unless told otherwise
+    let threads = cli.threads().unwrap_or_else(|| {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    });
+    let config = CrackConfig { blocks, space, threads, start_chunk };
+    logger.info(format_args!("{} Searching 2^{} keys on {} thread(s){}", tag,
+                             space.unknown_bits(), threads,
+                             if config.uses_complementation() {
+                                 ", complementation property halves the work"
+                             } else { "" }));
+    if start_chunk > 0 {
+        logger.info(format_args!("{} Resumed from chunk {} of {}",
+                                 tag, start_chunk, config.chunks()));
+    }
+    let report = crack::crack(config, PROGRESS_INTERVAL, |progress| {
+        logger.info(format_args!(
+            "{} {:.2}% ({} of {} keys), {:.2} Mkeys/s, {} s left", tag,
+            progress.tested as f64 * 100.0 / progress.total as f64, progress.tested,
+            progress.total, progress.keys_per_sec() / 1e6,
+            progress.remaining().map_or("?".to_string(), |r| format!("{:.0}", r.as_secs_f64()))));
+        if let Err(error) = save_checkpoint(progress.resume_chunk) {
+            logger.always(format_args!("{} <checkpoint isn't saved> {}", tag, error));
+        }
+    });
+    logger.verbose(format_args!("{} Elapsed: {:.3} s", tag,
+                                report.progress.elapsed.as_secs_f64()));
+    match report.key {
+        Some(key) => println!("Key found: {:#018X}", key),
+        None => {
+            println!("Key not found");
+            std::process::exit(1);
+        },
     }
-    
 }
@@ -26,7 +26,7 @@ pub mod idx_from_low {
     #[inline]
     pub fn bit_lower_mask(bit: u32) -> u64 {
         if bit < BITS_IN_INPUT { bit_mask(bit) - 1 }
-        else { std::u64::MAX }
+        else { u64::MAX }
     }
 
     /// Creates a binary mask, that spans from 63th bit to ‘bit’
@@ -34,7 +34,7 @@ pub mod idx_from_low {
     /// @returns such binary mask
     #[inline]
     pub fn bit_higher_mask(bit: u32) -> u64 {
-        std::u64::MAX - bit_lower_mask(bit + 1)
+        u64::MAX - bit_lower_mask(bit + 1)
     }
 
     /// Creates a binary mask, that spans from ‘begin_bit’ inclusive
@@ -109,13 +109,13 @@ pub mod idx_from_low {
     /// @returns a tuple with a number with dropped bits, and count of
     /// total bits dropped
     // TODO unordered, non unique indices support?
-    pub fn drop_bits<'a, T>(number: u64, ordered_drop_indices: T, size_bits: u32) -> (u64, u32)
+    pub fn drop_bits<T>(number: u64, ordered_drop_indices: T, size_bits: u32) -> (u64, u32)
     where T: Iterator<Item = u32> {
         let mut it = ordered_drop_indices.into_iter()
             .take_while(|&e| e < size_bits)
             .enumerate();
         let first = it.next();
-        if let None = first {
+        if first.is_none() {
             return (number, BITS_IN_INPUT);
         }
         let mut prev_drop_bit = first.unwrap().1;
@@ -171,7 +171,7 @@ pub mod idx_from_low {
         let cutoff_begin = end_bit - shift_for;
         let cutoff_mask = bit_range_mask(cutoff_begin, end_bit)?;
         let remain_mask = bit_range_mask(begin_bit, cutoff_begin)?;
-        let outer_mask = std::u64::MAX - (cutoff_mask | remain_mask);
+        let outer_mask = u64::MAX - (cutoff_mask | remain_mask);
         let cutoff_rotated = (number & cutoff_mask) >> (cutoff_begin - begin_bit);
         let remain_shifted = (number & remain_mask) << shift_for;
         let outer = number & outer_mask;
@@ -197,7 +197,7 @@ pub mod idx_from_low {
         let cutoff_begin = begin_bit + shift_for;
         let cutoff_mask = bit_range_mask(begin_bit, cutoff_begin)?;
         let remain_mask = bit_range_mask(cutoff_begin, end_bit)?;
-        let outer_mask = std::u64::MAX - (cutoff_mask | remain_mask);
+        let outer_mask = u64::MAX - (cutoff_mask | remain_mask);
         let cutoff_rotated = (number & cutoff_mask) << (end_bit - cutoff_begin);
         let remain_shifted = (number & remain_mask) >> shift_for;
         let outer = number & outer_mask;
@@ -284,7 +284,6 @@ pub mod idx_from_high {
         //println!("{}", index_from_end!(bit_idx, size_bits));
         low::get_bit(number, index_from_end!(bit_idx, size_bits), size_bits) 
     }
-    #[inline]
 
     /// Discard all bits in ‘number’ but specified range of
     /// bits. Assumes that number is ‘size_bits’ wide, so requesting
@@ -316,7 +315,7 @@ pub mod idx_from_high {
     /// @returns a tuple with a number with dropped bits, and count of
     /// total bits dropped
     #[inline]
-    pub fn drop_bits<'a, T>(number: u64, ordered_drop_indices: T, size_bits: u32) -> (u64, u32)
+    pub fn drop_bits<T>(number: u64, ordered_drop_indices: T, size_bits: u32) -> (u64, u32)
     where T: DoubleEndedIterator<Item = u32>{
         let recalculated_indices = ordered_drop_indices
             .rev()
@@ -383,6 +382,8 @@ pub mod idx_from_high {
  }
 
 #[cfg(test)]
+// bits of the literals are grouped by their meaning, not by four
+#[allow(clippy::unusual_byte_groupings)]
 mod test {
     use super::*;
     #[test]
@@ -395,7 +396,7 @@ mod test {
         assert_eq!(bit_lower_mask(5),  0b_01_1111, "mask of 5 first bits");
         assert_eq!(bit_lower_mask(0),  0b_00_0000, "mask of 0 first bits");
         assert_eq!(bit_lower_mask(63), 0x7F_FF_FF_FF_FF_FF_FF_FF, "mask of 63 first bits");
-        assert_eq!(bit_lower_mask(64), std::u64::MAX, "mask of all bits");
+        assert_eq!(bit_lower_mask(64), u64::MAX, "mask of all bits");
 
         assert_eq!(bit_range_mask(3, 5), Some(0b_0001_1000), "simple mask 2 bits wide");
         assert_eq!(bit_range_mask(2, 6), Some(0b_0011_1100), "simple mask 4 bits wide");
@@ -426,7 +427,7 @@ mod test {
         assert_eq!(rotate_range_to_high(0b00101, 0, 0, 5, BITS_IN_INPUT),
                    None, "end bit is zero, then last bit is what?");
         
-        let big_val = std::u64::MAX - 7;
+        let big_val = u64::MAX - 7;
         assert_eq!(rotate_range_to_high(big_val, 0, 64, 5, BITS_IN_INPUT),
                    Some(big_val.rotate_left(5)),
                    "shifts over type boundaries");
@@ -454,17 +455,17 @@ mod test {
             "Dropping one lowest bit"
         );
         assert_eq!(
-            drop_bits(0b_0101_0111_0110, vec![0,1,2,3,5,60,61,62,63,64].iter().cloned(), BITS_IN_INPUT),
+            drop_bits(0b_0101_0111_0110, [0,1,2,3,5,60,61,62,63,64].iter().cloned(), BITS_IN_INPUT),
             (0b_0000_0010_1011, 55),
             "Dropping many bits from beginning and end"
         );
         assert_eq!(
-            drop_bits(std::u64::MAX, (0..100).into_iter(), BITS_IN_INPUT),
+            drop_bits(u64::MAX, (0..100), BITS_IN_INPUT),
             (0, 0),
             "Drop all bits"
         );
         assert_eq!(
-            idx_from_high::drop_bits(std::u64::MAX, (0..100).into_iter(), BITS_IN_INPUT),
+            idx_from_high::drop_bits(u64::MAX, (0..100), BITS_IN_INPUT),
             (0, 0),
             "Dropp all bits in a high-bit-numeration notation"
         );
@@ -1,21 +1,76 @@
 use crate::ui::key_parsing;
+use crate::data_io;
 use std::path::PathBuf;
 use std::str::FromStr;
 use crate::reinterpret_bytes;
+use crate::des::analysis::{differential_attack, linear_attack, meet_in_the_middle};
+use crate::des::sdes;
+use std::io::Write;
+use super::Logger;
 
 pub static USAGE_MESSAGE: &str  = 
-    "\nUSAGE: des {{src_file}} {{dst_file}}\n\n\
-     * these paths can't be the same, and src_file should exist\n\
+    "\nUSAGE: des {{src_file}} {{dst_file}}\n\
+     \x20      des --in-place {{src_path}}\n\
+     \x20      des -o {{dst_dir}} {{src_path}} {{src_path}}..\n\
+     \x20      des --recursive {{src_dir}} {{dst_dir}}\n\
+     \x20      des bench [--size MB] [--json FILE]\n\
+     \x20      des explain --block HEX [-k KEY] [--markdown]\n\
+     \x20      des analyze sbox --ddt|--lat [--sboxes FILE]\n\
+     \x20                       [--csv FILE] [--json FILE]\n\
+     \x20      des attack linear [-r N] [--pairs N,N..] [--trials N]\n\
+     \x20      des attack differential [-r N] [--pairs N] [--trials N]\n\
+     \x20      des attack mitm [--bits N,N..] [--pairs N] [--trials N]\n\
+     \x20      des crack --plaintext HEX --ciphertext HEX\n\
+     \x20                [--key-pattern PATTERN] [--checkpoint FILE]\n\n\
+     * these paths can't be the same, and src_file should exist,\n\
+     \x20 existing dst_file is only overwritten with -f,\n\
+     \x20 '-' stands for stdin / stdout\n\
      * different flags are allowed between these tokens\n\
      (add -h/--help to command to find flags list)";
 
 pub static HELP_MESSAGE: &str =
-    "\nUSAGE: des {{src_path}} {{dst_path}}\n\n\
-     * these paths can't be the same, and src_path should exist\n\
+    "\nUSAGE: des {{src_path}} {{dst_path}}\n\
+     \x20      des --in-place {{src_path}}\n\
+     \x20      des -o {{dst_dir}} {{src_path}} {{src_path}}..\n\
+     \x20      des --recursive {{src_dir}} {{dst_dir}}\n\
+     \x20      des bench [--size MB] [--json FILE]\n\
+     \x20      des explain --block HEX [-k KEY] [--markdown]\n\
+     \x20      des analyze sbox --ddt|--lat [--sboxes FILE]\n\
+     \x20                       [--csv FILE] [--json FILE]\n\
+     \x20      des attack linear [-r N] [--pairs N,N..] [--trials N]\n\
+     \x20      des attack differential [-r N] [--pairs N] [--trials N]\n\
+     \x20      des attack mitm [--bits N,N..] [--pairs N] [--trials N]\n\
+     \x20      des crack --plaintext HEX --ciphertext HEX\n\
+     \x20                [--key-pattern PATTERN] [--checkpoint FILE]\n\n\
+     * these paths can't be the same, and src_path should exist,\n\
+     \x20 existing dst_path is only overwritten with -f,\n\
+     \x20 '-' stands for stdin / stdout\n\
      * different flags are allowed between these tokens\n\n\
      Available flags:\n\
      -h / --help \n\
      to print this message \n\n\
+     --stdout \n\
+     program will write the result to stdout \n\
+     instead of dst_file (as if it's '-'), \n\
+     messages always go to stderr \n\n\
+     --in-place \n\
+     program will replace src_path by the result \n\
+     (through a temporary file of the same \n\
+     directory, the original is untouched on \n\
+     errors), no dst_path is given \n\n\
+     -o / --output-dir DIR \n\
+     every free parameter is a src_path (a \n\
+     pattern with '*' and '?' in the file name \n\
+     is expanded), each one is transformed into \n\
+     DIR ('.des' is appended to encrypted names \n\
+     and removed from decrypted), failed files \n\
+     don't stop the others \n\n\
+     --recursive \n\
+     src_path is a directory, every file of its \n\
+     tree is transformed into the same place of \n\
+     dst_path directory ('.des' is appended to \n\
+     encrypted names and removed from decrypted), \n\
+     failed files don't stop the others \n\n\
      -e / --encrypt \n\
      program will perform encryption of src_file \n\n\
      -d / --decrypt \n\
@@ -27,24 +82,149 @@ pub static HELP_MESSAGE: &str =
      -k / --key \"KEY-HEX-STRING\" \n\n\
      program will perform using specified key \n\
      (which should contain only \n\
-     a-f, A-F, 0-9, '-' chars) \n\n\
+     a-f, A-F, 0-9, '-' chars), DES-X key has \n\
+     three such parts: DES key, pre-whitening and \n\
+     post-whitening keys (separated by ':') \n\n\
+     --cipher NAME \n\
+     program will use the cipher NAME instead of \n\
+     DES: des, desx (DES with key whitening), \n\
+     sdes (Simplified DES of 10 bit key and \n\
+     8 bit block, its key and blocks are also \n\
+     accepted as binary digits) \n\n\
+     --cipher-def FILE \n\
+     program will use DES with the tables from \n\
+     FILE (see bin/des.def for the format), \n\
+     explain and analyze run on these tables, \n\
+     bench, attack and crack don't accept them \n\n\
      -b / --big-endian \n\
-     program will generate dst_file using \n\
-     big-endian byte order for each 64-bit block \n\n\
+     program will read src_file and write \n\
+     dst_file by 64-bit blocks in big-endian \n\
+     byte order: the first byte of every 8 is \n\
+     the highest one, as in DES standard \n\
+     (default) \n\n\
      -l / --little-endian \n\
-     program will generate dst_file using \n\
-     little-endian byte order for each 64-bit block \n\n\
+     program will read src_file and write \n\
+     dst_file by 64-bit blocks in little-endian \n\
+     byte order: the first byte of every 8 is \n\
+     the lowest one, as little-endian machines \n\
+     load u64 from memory \n\n\
+     -j / --threads N \n\
+     program will encrypt / decrypt blocks on N \n\
+     threads at once (output is the same as \n\
+     with a single thread), files are transformed \n\
+     on 1 thread by default, crack and bench use \n\
+     all the cores \n\n\
+     -r / --rounds N \n\
+     program will use DES reduced or extended to \n\
+     N rounds (standard DES has 16) \n\n\
      -v / --verbose \n\
      program will output as much as possible info \n\
-     about its execution process in standard output \n\n\
+     about its execution process (mode, padding, \n\
+     sizes, timings) in standard error \n\n\
      -s / --silent \n\
      program will output no information \n\
-     in standard output \n\n\
-     --force \n\
-     program will answer 'Yes' on any uncertainty \n\
-     it would normally complain about and will \n\
-     execute as much as it can unless any fatal \n\
-     error occurs
+     in standard error \n\n\
+     bench \n\
+     instead of processing a file, program will \n\
+     measure throughput of DES and 3DES in ECB, CBC \n\
+     and CTR modes with every engine (-j sets threads \n\
+     of the threaded one, default is all cores) and \n\
+     the cost of key scheduling \n\n\
+     --size MB \n\
+     amount of data to process in each measurement \n\
+     of bench (default 1) \n\n\
+     --json FILE \n\
+     bench report (or analysis) will also be \n\
+     written in JSON format to FILE \n\n\
+     explain \n\
+     instead of processing a file, program will \n\
+     print every stage of encryption of a single \n\
+     block (-k and -r are taken into account) \n\n\
+     --block \"BLOCK-HEX-STRING\" \n\
+     64 bit block to explain (16 hex digits, \n\
+     '-' chars are allowed, or 8 binary digits \n\
+     with --cipher sdes) \n\n\
+     --markdown \n\
+     explanation will be formatted as Markdown \n\
+     instead of plain text \n\n\
+     analyze sbox \n\
+     instead of processing a file, program will \n\
+     analyze the S-boxes, the cipher uses \n\n\
+     --ddt \n\
+     S-box analysis will print difference \n\
+     distribution tables, max differential \n\
+     probabilities and checks of design criteria \n\n\
+     --lat \n\
+     S-box analysis will print linear approximation \n\
+     tables and the highest bias approximations \n\n\
+     --top N \n\
+     S-box analysis will list N approximations \n\
+     with the highest bias (default 10) \n\n\
+     --sboxes FILE \n\
+     S-box analysis will run on S-boxes from FILE \n\
+     (4 rows of 16 numbers per S-box, as in the \n\
+     spec) instead of the standard ones, it can't \n\
+     be combined with --cipher-def \n\n\
+     attack linear \n\
+     instead of processing a file, program will \n\
+     run Matsui's Algorithms 1 and 2 against DES \n\
+     reduced to -r rounds (1 to 8, default 8) on \n\
+     random keys and report their success rates \n\n\
+     attack differential \n\
+     instead of processing a file, program will \n\
+     recover keys of DES reduced to -r rounds \n\
+     (4 to 6, default 6) by Biham and Shamir's \n\
+     chosen plaintext attack and report them \n\n\
+     attack mitm \n\
+     instead of processing a file, program will \n\
+     recover both keys of 2DES by the meet-in-the- \n\
+     middle attack and report its time and memory \n\n\
+     --bits N,N.. \n\
+     numbers of unknown bits of each 2DES key, the \n\
+     attack is tried with (up to 24, default is \n\
+     8,12,16,20) \n\n\
+     --pairs N,N.. \n\
+     numbers of known plaintexts, the attack is \n\
+     tried with (default is picked by the bias); \n\
+     a single number of chosen pairs per \n\
+     characteristic for the differential attack \n\n\
+     --trials N \n\
+     number of random keys, the attack is tried \n\
+     on (default 8) \n\n\
+     --seed N \n\
+     seed of random keys and plaintexts of the \n\
+     attack (default is taken from the clock) \n\n\
+     crack \n\
+     instead of processing a file, program will \n\
+     search the key, which encrypts the known \n\
+     plaintext block into the known ciphertext \n\
+     block, on all cores (or -j threads) \n\n\
+     --plaintext HEX / --ciphertext HEX \n\
+     known pair of blocks for crack (8 binary \n\
+     digits each with --cipher sdes, all the \n\
+     keys, matching the pair, are printed) \n\n\
+     --complement HEX \n\
+     ciphertext of the complemented plaintext, \n\
+     if it's known, halves the search of the \n\
+     whole key space \n\n\
+     --key-pattern PATTERN \n\
+     key digits, which are known, '?' for the \n\
+     unknown ones (e.g. 0ACB-38C4-9EA3-????), \n\
+     default is the whole key space \n\n\
+     --key-mask HEX \n\
+     the same, but unknown bits are set in the \n\
+     mask, and known ones are taken from -k \n\n\
+     --checkpoint FILE \n\
+     crack will save its progress to FILE, and \n\
+     resume from it, if FILE exists \n\n\
+     --csv FILE \n\
+     analysis tables will also be written in CSV \n\
+     format to FILE (--json FILE works as well), \n\
+     only one of --ddt / --lat can be exported \n\n\
+     -f / --force \n\
+     program will overwrite an existing dst_path \n\
+     (or files in the output directory), which \n\
+     it would normally refuse to touch
      ";
 
 /// Possible actions user may desire, namely
@@ -52,16 +232,24 @@ pub static HELP_MESSAGE: &str =
 /// - DES decryption
 /// - TripleDES encryption
 /// - TripleDES decryption
-#[derive(Copy, Clone)]
+/// - throughput measurement
+/// - step by step explanation of a single block
+/// - cryptanalysis of the cipher’s parts
+/// - demonstration of attacks on reduced rounds
+/// - key search by a known block
+#[derive(Copy, Clone, Default)]
+#[allow(clippy::enum_variant_names)]
 pub enum Action {
+    #[default]
     EncryptFile,
     DecryptFile,
     TripleEncryptFile,
     TripleDecryptFile,
-}
-
-impl Default for Action {
-    fn default() -> Self { return Action::EncryptFile; }
+    Benchmark,
+    Explain,
+    Analyze,
+    Attack,
+    Crack,
 }
 
 // Delegation of action’s parsing to the action class itself
@@ -73,25 +261,94 @@ impl FromStr for Action {
             "-d" | "--decrypt" => Ok(Action::DecryptFile),
             "-te" | "--triple-encrypt" => Ok(Action::TripleEncryptFile),
             "-td" | "--triple-decrypt" => Ok(Action::TripleDecryptFile),
+            "bench" => Ok(Action::Benchmark),
+            "explain" => Ok(Action::Explain),
+            "analyze" => Ok(Action::Analyze),
+            "attack" => Ok(Action::Attack),
+            "crack" => Ok(Action::Crack),
             _ => Err(()),
         }
     }
 } 
 
 
+/// Part of the cipher to analyze
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AnalysisTarget {
+    SBox,
+}
+
+impl FromStr for AnalysisTarget {
+    type Err = ();
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "sbox" => Ok(AnalysisTarget::SBox),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Kind of cryptanalytic attack to demonstrate
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AttackKind {
+    Linear,
+    Differential,
+    MeetInTheMiddle,
+}
+
+impl FromStr for AttackKind {
+    type Err = ();
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "linear" => Ok(AttackKind::Linear),
+            "differential" => Ok(AttackKind::Differential),
+            "mitm" => Ok(AttackKind::MeetInTheMiddle),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Block cipher, which transforms files
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Cipher {
+    #[default]
+    Des,
+    DesX,
+    SDes,
+}
+
+impl Cipher {
+    /// Name of the cipher, as it’s reported to a user
+    pub fn name(&self) -> &'static str {
+        match self {
+            Cipher::Des => "DES",
+            Cipher::DesX => "DES-X",
+            Cipher::SDes => "S-DES",
+        }
+    }
+}
+
+impl FromStr for Cipher {
+    type Err = ();
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "des" => Ok(Cipher::Des),
+            "desx" => Ok(Cipher::DesX),
+            "sdes" => Ok(Cipher::SDes),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Level of logging, provided by CLI application
-/// TODO: not yet implemented
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum MessagingLevel {
     Verbose,
+    #[default]
     Normal,
     Silent,
 }
 
-impl Default for MessagingLevel {
-    fn default() -> Self { MessagingLevel::Normal }
-}
-
 // Delegation of messaging level’s parsing to the class itself
 impl FromStr for MessagingLevel {
     type Err = ();
@@ -104,8 +361,7 @@ impl FromStr for MessagingLevel {
     }
 }
 
-/// Endianess of created output file
-/// TODO: not yet implemented
+/// Byte order of 64 bit blocks in the input and output files
 pub struct Endianess {
     endianess: reinterpret_bytes::Endianess,
 }
@@ -134,8 +390,7 @@ impl FromStr for Endianess {
 }
 
 /// Is application allowed to do whatever it takes to create the
-/// output file (even through possible confusion or errors)
-/// TODO: not yet implemented
+/// output file (i.e. to overwrite an existing one)
 #[derive(Default)]
 pub struct Force {
     do_force: bool,
@@ -202,6 +457,22 @@ impl DataFlag for Key {
     }
 }
 
+/// \returns name of the file at ‘path’, as it’s reported to a user,
+/// or ‘stdio_name’, if the path stands for standard input / output
+fn stream_name(path: &std::path::Path, stdio_name: &str) -> String {
+    if data_io::is_stdio(&path) { format!("<{}>", stdio_name) }
+    else { path.display().to_string() }
+}
+
+/// Parses a block, given in command line: 16 hex digits, or 8 binary
+/// digits of an S-DES block
+/// \returns Option with the block
+fn parse_block<S: AsRef<str>>(block_str: &S) -> Option<u64> {
+    key_parsing::key_from_str(block_str)
+        .or_else(|_| key_parsing::binary_from_str(block_str, sdes::BLOCK_BITS))
+        .ok()
+}
+
 /// Command line interface for DES application, parses command line
 /// arguments into DES options
 #[derive(Default)]
@@ -212,28 +483,69 @@ pub struct Cli {
     pub action: Action,
     pub endianess: Endianess,
     pub messaging_level: MessagingLevel,
-    pub force: bool,
+    pub force: Force,
     pub help_requested: bool,
+    /// Number of threads, if it’s given (defaults depend on action)
+    pub threads: Option<usize>,
+    pub rounds: usize,
+    pub bench_size_mb: usize,
+    pub json_path: Option<PathBuf>,
+    pub block: Option<u64>,
+    pub markdown: bool,
+    pub analysis_target: Option<AnalysisTarget>,
+    pub ddt: bool,
+    pub lat: bool,
+    pub top: usize,
+    pub sboxes_path: Option<PathBuf>,
+    pub csv_path: Option<PathBuf>,
+    pub attack_kind: Option<AttackKind>,
+    pub pairs: Vec<usize>,
+    pub bits: Vec<u32>,
+    pub trials: usize,
+    pub seed: Option<u64>,
+    pub plaintext: Option<u64>,
+    pub ciphertext: Option<u64>,
+    pub complement: Option<u64>,
+    pub key_mask: Option<u64>,
+    pub checkpoint_path: Option<PathBuf>,
+    pub cipher: Cipher,
+    /// Pre- and post-whitening keys of DES-X
+    pub whitening: Option<(u64, u64)>,
+    /// Tables of a DES-like cipher, which replace the ones of DES
+    pub cipher_def_path: Option<PathBuf>,
+    /// Source and destination are directories, transformed file by file
+    pub recursive: bool,
+    /// Batch of source files (or patterns), all go to ‘dst_file_path’
+    /// directory
+    pub batch_paths: Vec<PathBuf>,
+    /// Source file is replaced by the result
+    pub in_place: bool,
 }
 
 impl Cli {
     /// Construct with default parameters
     /// @returns a new instance of Cli type with default settings
     pub fn new() -> Self {
-        Default::default()
+        Self { rounds: 16, bench_size_mb: 1, top: 10, trials: 8,
+               ..Default::default() }
     }
 
     /// Take iterator of strings and try parse as many CLI parameters
     /// as it can
     /// @returns Option with modified Cli instance in it. None denotes
     /// failure during parsing of arguments
-    pub fn parse_args<'a, T, S>(mut self, mut args: T) -> Option<Self>
+    pub fn parse_args<T, S>(mut self, mut args: T) -> Option<Self>
     where T : Iterator<Item=S>, S: AsRef<str> {
         // Skip executable name
         args.next()?;
         let mut dest_path_buf;
+        let mut dst_dir_path = None;
+        let mut output_dir_path: Option<PathBuf> = None;
         let mut is_action_specified = false;
         let mut is_endianess_specified = false;
+        let mut is_messaging_specified = false;
+        let mut is_rounds_specified = false;
+        let mut is_stdout_specified = false;
         let mut free_arg_cnt = 0;
         while let Some(flag) = args.next() {
             let flag = flag.as_ref();
@@ -243,11 +555,152 @@ impl Cli {
                 "-k" | "--key"=> {
                     // TODO: Cow possibility, inplace replace
                     let key_hex_str = &args.next()?;
-                    self.key = key_parsing::key_from_str(key_hex_str).ok()?;
+                    // DES-X key has three parts, S-DES key is binary
+                    match key_parsing::key_from_str(key_hex_str)
+                        .or_else(|_| key_parsing::binary_from_str(key_hex_str, sdes::KEY_BITS)) {
+                        Ok(key) => {
+                            self.key = key;
+                            self.whitening = None;
+                        },
+                        Err(_) => {
+                            let (key, pre, post) =
+                                key_parsing::desx_key_from_str(key_hex_str).ok()?;
+                            self.key = key;
+                            self.whitening = Some((pre, post));
+                        },
+                    }
+                },
+                "-j" | "--threads" => {
+                    let threads = args.next()?.as_ref().parse().ok()?;
+                    if threads == 0 { return None; }
+                    self.threads = Some(threads);
+                },
+                "-r" | "--rounds" => {
+                    self.rounds = args.next()?.as_ref().parse().ok()?;
+                    if self.rounds == 0 { return None; }
+                    is_rounds_specified = true;
+                },
+                "-v" | "--verbose" | "-s" | "--silent" => {
+                    if is_messaging_specified { return None; }
+                    is_messaging_specified = true;
+                    self.messaging_level = flag.parse().ok()?;
                 },
                 "-h" | "--help" => {
                     self.help_requested = true;
                 },
+                "--stdout" => {
+                    is_stdout_specified = true;
+                },
+                "-f" | "--force" => {
+                    self.force = flag.parse().ok()?;
+                },
+                "--recursive" => {
+                    self.recursive = true;
+                },
+                "--in-place" => {
+                    self.in_place = true;
+                },
+                "-o" | "--output-dir" => {
+                    if output_dir_path.is_some() { return None; }
+                    output_dir_path = Some(PathBuf::from(args.next()?.as_ref()));
+                },
+                "--size" => {
+                    self.bench_size_mb = args.next()?.as_ref().parse().ok()?;
+                    if self.bench_size_mb == 0 { return None; }
+                },
+                "--json" => {
+                    self.json_path = Some(PathBuf::from(args.next()?.as_ref()));
+                },
+                "--block" => {
+                    let block_hex_str = &args.next()?;
+                    self.block = Some(parse_block(block_hex_str)?);
+                },
+                "--markdown" => {
+                    self.markdown = true;
+                },
+                "--ddt" => {
+                    self.ddt = true;
+                },
+                "--lat" => {
+                    self.lat = true;
+                },
+                "--top" => {
+                    self.top = args.next()?.as_ref().parse().ok()?;
+                },
+                "--pairs" => {
+                    self.pairs = args.next()?.as_ref().split(',')
+                        .map(|pairs| pairs.trim().parse().ok())
+                        .collect::<Option<_>>()?;
+                    if self.pairs.contains(&0) { return None; }
+                },
+                "--bits" => {
+                    self.bits = args.next()?.as_ref().split(',')
+                        .map(|bits| bits.trim().parse().ok())
+                        .collect::<Option<_>>()?;
+                },
+                "--trials" => {
+                    self.trials = args.next()?.as_ref().parse().ok()?;
+                    if self.trials == 0 { return None; }
+                },
+                "--seed" => {
+                    self.seed = Some(args.next()?.as_ref().parse().ok()?);
+                },
+                "--sboxes" => {
+                    self.sboxes_path = Some(PathBuf::from(args.next()?.as_ref()));
+                },
+                "--csv" => {
+                    self.csv_path = Some(PathBuf::from(args.next()?.as_ref()));
+                },
+                "--plaintext" => {
+                    self.plaintext = Some(parse_block(&args.next()?)?);
+                },
+                "--ciphertext" => {
+                    self.ciphertext = Some(parse_block(&args.next()?)?);
+                },
+                "--complement" => {
+                    self.complement = Some(key_parsing::key_from_str(&args.next()?).ok()?);
+                },
+                "--key-pattern" => {
+                    let (known, unknown) = key_parsing::key_pattern_from_str(&args.next()?).ok()?;
+                    self.key = known;
+                    self.key_mask = Some(unknown);
+                },
+                "--key-mask" => {
+                    self.key_mask = Some(key_parsing::key_from_str(&args.next()?).ok()?);
+                },
+                "--cipher" => {
+                    self.cipher = args.next()?.as_ref().parse().ok()?;
+                },
+                "--cipher-def" => {
+                    self.cipher_def_path = Some(PathBuf::from(args.next()?.as_ref()));
+                },
+                "--checkpoint" => {
+                    self.checkpoint_path = Some(PathBuf::from(args.next()?.as_ref()));
+                },
+                // Subcommands are only recognized in place of the
+                // first free parameter
+                "bench" if free_arg_cnt == 0 && !is_action_specified => {
+                    is_action_specified = true;
+                    self.action = Action::Benchmark;
+                },
+                "explain" if free_arg_cnt == 0 && !is_action_specified => {
+                    is_action_specified = true;
+                    self.action = Action::Explain;
+                },
+                "analyze" if free_arg_cnt == 0 && !is_action_specified => {
+                    is_action_specified = true;
+                    self.action = Action::Analyze;
+                    self.analysis_target = Some(args.next()?.as_ref().parse().ok()?);
+                },
+                "attack" if free_arg_cnt == 0 && !is_action_specified => {
+                    is_action_specified = true;
+                    self.action = Action::Attack;
+                    self.attack_kind = Some(args.next()?.as_ref().parse().ok()?);
+                },
+                "crack" if free_arg_cnt == 0 && !is_action_specified => {
+                    is_action_specified = true;
+                    self.action = Action::Crack;
+                },
                 "-d" | "--decrypt" => {
                     if is_action_specified { return None; }
                     is_action_specified = true;
@@ -258,44 +711,36 @@ impl Cli {
                     is_action_specified = true;
                     self.action = Action::EncryptFile
                 },
-                // "-b" | "--big-endian" => {
-                //     if is_endianess_specified { return None; }
-                //     is_endianess_specified = true;
-                //     self.endianess = Endianess::Big;
-                // }
-                // "-l" | "--little-endian" => {
-                //     if is_endianess_specified { return None; }
-                //     is_endianess_specified = true;
-                //     self.endianess = Endianess::Little;
-                // }
+                "-b" | "--big-endian" | "-l" | "--little-endian" => {
+                    if is_endianess_specified { return None; }
+                    is_endianess_specified = true;
+                    self.endianess = flag.parse().ok()?;
+                },
 
                 // Parameters, that can be placed anywhere between
                 // flags, but they’re in a strict order among themselves 
                 free_arg => {
                     free_arg_cnt += 1;
+                    self.batch_paths.push(PathBuf::from(free_arg));
                     match free_arg_cnt {
                         // input file parameter
                         1 => { 
                             self.src_file_path = PathBuf::from(free_arg);
-                            if !self.src_file_path.is_file() {return None;}
-                            // Default state
+                            // Default state, stdin goes to stdout
+                            if data_io::is_stdio(&self.src_file_path) {
+                                self.dst_file_path = PathBuf::from(data_io::STDIO_PATH);
+                                continue;
+                            }
+                            // file or directory is checked, when all flags
+                            // are known (a batch has patterns)
                             dest_path_buf = PathBuf::from(&self.src_file_path);
                             dest_path_buf.set_extension("des");
                             self.dst_file_path = dest_path_buf;
                         },
-                        // output file parameter
+                        // output file (or directory) parameter, it's
+                        // resolved, when all flags are known
                         2 => { 
-                            let mut dst_path = PathBuf::from(free_arg);
-                            if dst_path.is_dir() {
-                                dst_path.set_file_name(
-                                    self.src_file_path.file_name().unwrap());
-                                dst_path.set_extension("des");
-                            } else if let Some(_) = dst_path.file_name() {
-                                if let None = dst_path.extension() {
-                                    dst_path.set_extension("des");
-                                }
-                            }
-                            self.dst_file_path = dst_path
+                            dst_dir_path = Some(PathBuf::from(free_arg));
                         },
                         _ => (),
                     }
@@ -303,14 +748,104 @@ impl Cli {
                 },
             }
         }
+        // Output path can’t be given twice
+        if is_stdout_specified {
+            if free_arg_cnt >= 2 { return None; }
+            self.dst_file_path = PathBuf::from(data_io::STDIO_PATH);
+        }
+        // Batch goes to the output directory, a single file goes to
+        // the second parameter, a directory to a given directory
+        if let Some(output_dir_path) = output_dir_path {
+            if is_stdout_specified || self.recursive || self.in_place || data_io::is_stdio(&output_dir_path)
+                || self.batch_paths.iter().any(data_io::is_stdio) { return None; }
+            self.dst_file_path = output_dir_path;
+        } else if self.recursive {
+            self.batch_paths.clear();
+            let dst_dir_path = dst_dir_path?;
+            if self.in_place || !self.src_file_path.is_dir() || data_io::is_stdio(&dst_dir_path) { return None; }
+            self.dst_file_path = dst_dir_path;
+        } else {
+            self.batch_paths.clear();
+            if free_arg_cnt > 2 { return None; }
+            if free_arg_cnt >= 1 && !data_io::is_stdio(&self.src_file_path)
+                && !self.src_file_path.is_file() { return None; }
+            if let Some(mut dst_path) = dst_dir_path {
+                if data_io::is_stdio(&dst_path) {
+                    // stays as it is
+                } else if dst_path.is_dir() {
+                    // stdin has no name to take
+                    if data_io::is_stdio(&self.src_file_path) { return None; }
+                    dst_path.set_file_name(self.src_file_path.file_name()?);
+                    dst_path.set_extension("des");
+                } else if dst_path.file_name().is_some()
+                    && dst_path.extension().is_none() {
+                        dst_path.set_extension("des");
+                    }
+                self.dst_file_path = dst_path;
+            }
+            // The only parameter is both source and destination
+            if self.in_place {
+                if free_arg_cnt != 1 || is_stdout_specified
+                    || data_io::is_stdio(&self.src_file_path) { return None; }
+                self.dst_file_path = self.src_file_path.clone();
+            }
+        }
+        // Attacks are demonstrated on reduced rounds
+        if let (Action::Attack, false) = (self.action, is_rounds_specified) {
+            self.rounds = match self.attack_kind {
+                Some(AttackKind::Differential) => differential_attack::MAX_ROUNDS,
+                _ => linear_attack::MAX_ROUNDS,
+            };
+        }
         // Requested help allows misuse in other flags and parameters
-        if self.help_requested || free_arg_cnt >= 1 { Some(self) }
+        // S-DES key and blocks are narrow
+        let fits_sdes = |value: u64, size_bits: u32| value >> size_bits == 0;
+        let is_complete = match self.action {
+            // These run on the DES engine itself, not on replaced tables
+            Action::Benchmark | Action::Attack | Action::Crack
+                if self.cipher_def_path.is_some() => false,
+            Action::Benchmark => true,
+            Action::Explain if self.cipher == Cipher::SDes => self.block
+                .is_some_and(|block| fits_sdes(block, sdes::BLOCK_BITS))
+                && fits_sdes(self.key, sdes::KEY_BITS) && self.cipher_def_path.is_none(),
+            Action::Explain => self.block.is_some(),
+            // Both tables can’t be exported to the same file, S-boxes
+            // come either from a file or from the cipher’s tables
+            Action::Analyze => (self.ddt || self.lat) && !(self.ddt && self.lat
+                && (self.csv_path.is_some() || self.json_path.is_some()))
+                && !(self.sboxes_path.is_some() && self.cipher_def_path.is_some()),
+            Action::Attack => match self.attack_kind {
+                Some(AttackKind::Differential) => self.pairs.len() <= 1 &&
+                    (differential_attack::MIN_ROUNDS..=differential_attack::MAX_ROUNDS)
+                        .contains(&self.rounds),
+                Some(AttackKind::MeetInTheMiddle) => self.pairs.len() <= 1 &&
+                    self.bits.iter().all(|&bits| bits <= meet_in_the_middle::MAX_BITS),
+                _ => self.rounds <= linear_attack::MAX_ROUNDS,
+            },
+            Action::Crack if self.cipher == Cipher::SDes => self.checkpoint_path.is_none()
+                && [self.plaintext, self.ciphertext].iter()
+                    .all(|block| block.is_some_and(|block| fits_sdes(block, sdes::BLOCK_BITS))),
+            // Known blocks can be taken from a checkpoint
+            Action::Crack => (self.plaintext.is_some() && self.ciphertext.is_some())
+                || self.checkpoint_path.is_some(),
+            // DES-X needs the whitening keys, DES can’t take them,
+            // tables are only replaced in DES
+            Action::EncryptFile | Action::DecryptFile if self.cipher == Cipher::SDes =>
+                free_arg_cnt >= 1 && self.whitening.is_none()
+                    && self.cipher_def_path.is_none() && fits_sdes(self.key, sdes::KEY_BITS),
+            Action::EncryptFile | Action::DecryptFile => free_arg_cnt >= 1
+                && (self.cipher == Cipher::DesX) == self.whitening.is_some()
+                && (self.cipher == Cipher::Des || self.cipher_def_path.is_none()),
+            _ => free_arg_cnt >= 1 && self.cipher == Cipher::Des
+                && self.cipher_def_path.is_none(),
+        };
+        if self.help_requested || is_complete { Some(self) }
         else { None }
     }
 
     /// Set default key, if no key is given from command line arguments
     /// @returns Result with modified Cli instance in it (builder pattern)
-    pub fn default_key<'a, T>
+    pub fn default_key<T>
         (mut self, key: T) -> Result<Self, key_parsing::ParseKeyError>
     where T: AsRef<str> {
         // TODO: no details
@@ -332,38 +867,42 @@ impl Cli {
         self
     }
     /// User output: print help message
-    // TODO: accept stream, not just println
-    pub fn print_help() {
-        println!("{}", HELP_MESSAGE);
+    pub fn print_help<W: Write>(logger: &mut Logger<W>) {
+        logger.always(format_args!("{}", HELP_MESSAGE));
     }
 
     /// User output: print usage message
-    pub fn print_usage() {
-        println!("{}", USAGE_MESSAGE);
+    pub fn print_usage<W: Write>(logger: &mut Logger<W>) {
+        logger.always(format_args!("{}", USAGE_MESSAGE));
     }
 
     /// User output: print announcement message, about the start of
     /// DES encryption / decryption
-    pub fn announce_begin(&self) {
-        use Action::*;
-        let tag = match self.action {
-            EncryptFile | TripleEncryptFile  => "[ ENCRYPT ]",
-            DecryptFile | TripleDecryptFile  => "[ DECRYPT ]",
-        };
-        println!("{} Input  file: {}", tag, self.src_file_path.display());
-        println!("{} Output file: {}", tag, self.dst_file_path.display());
-        println!("{} Key = {:#018x}", tag, self.key)
+    pub fn announce_begin<W: Write>(&self, logger: &mut Logger<W>) {
+        let tag = self.tag();
+        logger.info(format_args!("{} Input  file: {}", tag, stream_name(&self.src_file_path, "stdin")));
+        logger.info(format_args!("{} Output file: {}", tag, stream_name(&self.dst_file_path, "stdout")));
+        logger.info(format_args!("{} Key = {:#018x}", tag, self.key));
     }
 
     /// User output: print announcement message, about the end of
     /// DES encryption / decryption
-    pub fn announce_end(&self) {
+    pub fn announce_end<W: Write>(&self, logger: &mut Logger<W>) {
+        logger.info(format_args!("{} Done", self.tag()));
+    }
+
+    /// @returns prefix of messages about the current action
+    pub fn tag(&self) -> &'static str {
         use Action::*;
-        let tag = match self.action {
-            EncryptFile | TripleEncryptFile => "[ ENCRYPT ]",
-            DecryptFile | TripleDecryptFile => "[ DECRYPT ]",
-        };
-        println!("{} Done", tag);
+        match self.action {
+            EncryptFile | TripleEncryptFile  => "[ ENCRYPT ]",
+            DecryptFile | TripleDecryptFile  => "[ DECRYPT ]",
+            Benchmark => "[ BENCH ]",
+            Explain => "[ EXPLAIN ]",
+            Analyze => "[ ANALYZE ]",
+            Attack => "[ ATTACK ]",
+            Crack => "[ CRACK ]",
+        }
     }
 
 }
@@ -376,4 +915,60 @@ impl Cli {
     pub fn dst_file_path(&self) -> &PathBuf { &self.dst_file_path }
     pub fn action(&self) -> Action { self.action }
     pub fn endianess(&self) -> reinterpret_bytes::Endianess { self.endianess.endianess }
+    pub fn messaging_level(&self) -> MessagingLevel { self.messaging_level }
+    pub fn threads(&self) -> Option<usize> { self.threads }
+    pub fn rounds(&self) -> usize { self.rounds }
+    pub fn bench_size_mb(&self) -> usize { self.bench_size_mb }
+    pub fn json_path(&self) -> Option<&PathBuf> { self.json_path.as_ref() }
+    pub fn block(&self) -> Option<u64> { self.block }
+    pub fn markdown(&self) -> bool { self.markdown }
+    pub fn analysis_target(&self) -> Option<AnalysisTarget> { self.analysis_target }
+    pub fn ddt(&self) -> bool { self.ddt }
+    pub fn lat(&self) -> bool { self.lat }
+    pub fn top(&self) -> usize { self.top }
+    pub fn sboxes_path(&self) -> Option<&PathBuf> { self.sboxes_path.as_ref() }
+    pub fn csv_path(&self) -> Option<&PathBuf> { self.csv_path.as_ref() }
+    pub fn attack_kind(&self) -> Option<AttackKind> { self.attack_kind }
+    pub fn pairs(&self) -> &[usize] { &self.pairs }
+    pub fn bits(&self) -> &[u32] { &self.bits }
+    pub fn trials(&self) -> usize { self.trials }
+    pub fn seed(&self) -> Option<u64> { self.seed }
+    pub fn plaintext(&self) -> Option<u64> { self.plaintext }
+    pub fn ciphertext(&self) -> Option<u64> { self.ciphertext }
+    pub fn complement(&self) -> Option<u64> { self.complement }
+    pub fn key_mask(&self) -> Option<u64> { self.key_mask }
+    pub fn checkpoint_path(&self) -> Option<&PathBuf> { self.checkpoint_path.as_ref() }
+    pub fn cipher(&self) -> Cipher { self.cipher }
+    pub fn whitening(&self) -> Option<(u64, u64)> { self.whitening }
+    pub fn cipher_def_path(&self) -> Option<&PathBuf> { self.cipher_def_path.as_ref() }
+    pub fn recursive(&self) -> bool { self.recursive }
+    /// @returns source paths of a batch, empty if it’s not a batch
+    pub fn batch_paths(&self) -> &[PathBuf] { &self.batch_paths }
+    pub fn in_place(&self) -> bool { self.in_place }
+    pub fn force(&self) -> bool { self.force.do_force }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn parse(args: &[&str]) -> Option<Cli> {
+        Cli::new().default_action(Action::EncryptFile)
+            .parse_args(std::iter::once("des").chain(args.iter().cloned()))
+    }
+
+    #[test]
+    fn test_directory_source() {
+        // existing directory as the destination, source has no file name
+        let dst = std::env::temp_dir();
+        let dst = dst.to_str().unwrap();
+        let recursive = parse(&["--recursive", "-e", ".", dst]).expect("Recursive mode of '.'");
+        assert_eq!(recursive.dst_file_path(), Path::new(dst), "Directory goes to the directory");
+        let flag_last = parse(&["-e", ".", dst, "--recursive"]).expect("Flags go anywhere");
+        assert_eq!(flag_last.dst_file_path(), Path::new(dst), "Flag after the paths");
+        let batch = parse(&["-e", "-o", dst, "."]).expect("Batch of '.'");
+        assert_eq!(batch.batch_paths(), [Path::new(".")]);
+        assert!(parse(&["-e", ".", dst]).is_none(), "Directory isn't a single file");
+    }
 }
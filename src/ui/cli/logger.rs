@@ -0,0 +1,90 @@
+//! User-facing messages of the application. Every message has a level
+//! of verbosity and is either written to the sink or dropped,
+//! depending on the level, which was requested by a user
+
+use std::fmt;
+use std::io::{self, Write};
+use super::MessagingLevel;
+
+/// Writes messages to any Write object (standard error by default),
+/// filtering them by messaging level
+pub struct Logger<W: Write> {
+    level: MessagingLevel,
+    sink: W,
+}
+
+impl Logger<io::Stderr> {
+    /// Construct logger, writing to standard error
+    /// @returns a new instance of Logger
+    pub fn stderr(level: MessagingLevel) -> Self {
+        Self::new(level, io::stderr())
+    }
+}
+
+impl<W: Write> Logger<W> {
+    /// Construct logger, writing to a given ‘sink’
+    /// @returns a new instance of Logger
+    pub fn new(level: MessagingLevel, sink: W) -> Self {
+        Self { level, sink }
+    }
+
+    /// Message, which was explicitly asked for (e.g. help), it’s
+    /// written on any level
+    pub fn always(&mut self, message: fmt::Arguments) {
+        self.write(message);
+    }
+
+    /// Message about the normal course of execution, it’s dropped in
+    /// silent mode
+    pub fn info(&mut self, message: fmt::Arguments) {
+        if !matches!(self.level, MessagingLevel::Silent) {
+            self.write(message);
+        }
+    }
+
+    /// Message with details of execution, it’s written only in verbose
+    /// mode
+    pub fn verbose(&mut self, message: fmt::Arguments) {
+        if self.is_verbose() {
+            self.write(message);
+        }
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        matches!(self.level, MessagingLevel::Verbose)
+    }
+
+    pub fn level(&self) -> MessagingLevel { self.level }
+
+    /// @returns the sink, messages were written to
+    pub fn into_sink(self) -> W { self.sink }
+
+    // Failure to report something shouldn’t fail the work itself, so
+    // errors of the sink are ignored
+    fn write(&mut self, message: fmt::Arguments) {
+        let _ = writeln!(self.sink, "{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_all(level: MessagingLevel) -> String {
+        let mut logger = Logger::new(level, vec![]);
+        logger.always(format_args!("always"));
+        logger.info(format_args!("info"));
+        logger.verbose(format_args!("verbose {}", 42));
+        String::from_utf8(logger.into_sink()).unwrap()
+    }
+
+    #[test]
+    fn test_messaging_levels() {
+        assert_eq!(log_all(MessagingLevel::Verbose), "always\ninfo\nverbose 42\n",
+                   "Verbose level should write everything");
+        assert_eq!(log_all(MessagingLevel::Normal), "always\ninfo\n",
+                   "Normal level should drop verbose messages");
+        assert_eq!(log_all(MessagingLevel::Silent), "always\n",
+                   "Silent level should write only requested messages");
+    }
+}
@@ -3,3 +3,6 @@
 
 pub mod cli_main;
 pub use self::cli_main::*;
+
+pub mod logger;
+pub use self::logger::*;
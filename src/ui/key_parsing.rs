@@ -35,6 +35,53 @@ pub fn key_from_str<S: AsRef<str>>
         u64::from_str_radix(&clean, 16).or(Err(NotHexFormat))
 }
 
+/// Takes a DES-X key (string) of three parts: DES key, pre-whitening
+/// and post-whitening keys, each of the same format as ‘key_from_str’.
+/// Parts are separated by ’:’, ’,’ or spaces, or just go one after
+/// another (48 hex digits)
+/// @returns a Result with the parts of the key in order
+pub fn desx_key_from_str<S: AsRef<str>>
+    (hex_str: &S) -> std::result::Result<(u64, u64, u64), ParseKeyError> {
+        let parts: Vec<&str> = hex_str.as_ref()
+            .split(|c: char| c == ':' || c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .collect();
+        let clean;
+        let parts = if parts.len() == 1 {
+            clean = parts[0].replace("-", "");
+            if clean.len() != 48 || !clean.is_ascii() { return Err(BadLength(48)); }
+            vec![&clean[..16], &clean[16..32], &clean[32..]]
+        } else {
+            parts
+        };
+        if parts.len() != 3 { return Err(BadLength(48)); }
+        Ok((key_from_str(&parts[0])?, key_from_str(&parts[1])?, key_from_str(&parts[2])?))
+}
+
+/// Takes a key pattern (string) of the same format as ‘key_from_str’,
+/// where also ’?’ stands for an unknown hex digit, parses it into the
+/// known part of the key and a mask of unknown bits
+/// @returns a Result with (known key bits, mask of unknown bits)
+pub fn key_pattern_from_str<S: AsRef<str>>
+    (pattern: &S) -> std::result::Result<(u64, u64), ParseKeyError> {
+        let known = key_from_str(&pattern.as_ref().replace('?', "0"))?;
+        let unknown = key_from_str(&pattern.as_ref().chars()
+            .map(|c| match c { '?' => 'F', '-' => '-', _ => '0' })
+            .collect::<String>())?;
+        Ok((known, unknown))
+}
+
+/// Takes a value (string) of exactly ‘size_bits’ binary digits (’-’
+/// and ’_’ chars are allowed as separators), the way textbook ciphers
+/// write their keys and blocks
+/// @returns a Result with the parsed value
+pub fn binary_from_str<S: AsRef<str>>
+    (bin_str: &S, size_bits: u32) -> std::result::Result<u64, ParseKeyError> {
+        let clean = bin_str.as_ref().replace(['-', '_'], "").trim().to_string();
+        if clean.len() != size_bits as usize { return Err(BadLength(size_bits as usize)); }
+        u64::from_str_radix(&clean, 2).or(Err(NotHexFormat))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,4 +106,48 @@ mod tests {
                    Ok(0xabcd_ffee_0000_1234),
                    "Failed to parse a key with different characters");
 }
+
+    #[test]
+    fn test_desx_key_from_str() {
+        let key = Ok((0x0123_4567_89AB_CDEF, 0xF1E0_D3C2_B5A4_9786, 0xFEDC_BA98_7654_3210));
+        assert_eq!(desx_key_from_str(&"0123456789ABCDEF:F1E0D3C2B5A49786:FEDCBA9876543210"),
+                   key, "Parts separated by colons");
+        assert_eq!(desx_key_from_str(&"0123-4567-89AB-CDEF, F1E0-D3C2-B5A4-9786, FEDC-BA98-7654-3210"),
+                   key, "Parts separated by commas, with dashes");
+        assert_eq!(desx_key_from_str(&"0123456789abcdeff1e0d3c2b5a49786fedcba9876543210"),
+                   key, "Parts one after another, as OpenSSL takes them");
+        assert_eq!(desx_key_from_str(&"0123456789ABCDEF:F1E0D3C2B5A49786"),
+                   Err(BadLength(48)), "Missing part");
+        assert_eq!(desx_key_from_str(&"0123456789ABCDEF:F1E0D3C2B5A49786:FEDCBA987654321Z"),
+                   Err(NotHexFormat), "Not hex character in a part");
+    }
+
+    #[test]
+    fn test_key_pattern_from_str() {
+        assert_eq!(key_pattern_from_str(&"0ACB-38C4-9EA3-1C??"),
+                   Ok((0x0ACB_38C4_9EA3_1C00, 0xFF)),
+                   "Unknown digits in the end");
+        assert_eq!(key_pattern_from_str(&"????????????????"),
+                   Ok((0, !0)),
+                   "Whole key is unknown");
+        assert_eq!(key_pattern_from_str(&"0?CB-38C4-9EA3-1C71"),
+                   Ok((0x00CB_38C4_9EA3_1C71, 0x0F00_0000_0000_0000)),
+                   "Unknown digit in the middle");
+        assert_eq!(key_pattern_from_str(&"0?CB-38C4-9EA3-1C7"),
+                   Err(BadLength(16)),
+                   "Pattern of wrong length");
+        assert_eq!(key_pattern_from_str(&"0?CB-38C4-9EA3-1C7Z"),
+                   Err(NotHexFormat),
+                   "Not hex character in pattern");
+    }
+
+    #[test]
+    fn test_binary_from_str() {
+        assert_eq!(binary_from_str(&"1010000010", 10), Ok(0b1010000010));
+        assert_eq!(binary_from_str(&"10100-00010", 10), Ok(0b1010000010),
+                   "Separators are skipped");
+        assert_eq!(binary_from_str(&"1001_0111", 8), Ok(0b10010111));
+        assert_eq!(binary_from_str(&"101", 8), Err(BadLength(8)));
+        assert_eq!(binary_from_str(&"10010112", 8), Err(NotHexFormat));
+    }
 }